@@ -0,0 +1,230 @@
+use super::{
+    settings::CardBack,
+    theme::{self, Palette},
+};
+use crate::core::{
+    cards::{Card, Rank, Suit},
+    tiles::AnyTile,
+    CardProxy,
+};
+use std::time::Duration;
+use tui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+pub const FLIP_TIME: Duration = Duration::from_millis(240);
+const FLIP_FRAMES: u32 = 6;
+
+/// Smallest tile a card gets its full face in, corners and big pip.
+pub const FULL_SIZE: (u16, u16) = (9, 8);
+
+/// One board slot: a card face up or down, or nothing once it's been taken.
+pub struct CardTile<'a> {
+    card: Option<&'a CardProxy>,
+    back: CardBack,
+    palette: &'a Palette,
+    selected: bool,
+    turned: Option<Duration>,
+}
+
+impl<'a> CardTile<'a> {
+    pub fn new(card: Option<&'a CardProxy>, back: CardBack, palette: &'a Palette) -> Self {
+        Self {
+            card,
+            back,
+            palette,
+            selected: false,
+            turned: None,
+        }
+    }
+
+    pub fn selected(mut self, selected: bool) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    /// How long ago the card was turned over, to animate the flip.
+    pub fn turned(mut self, turned: Option<Duration>) -> Self {
+        self.turned = turned;
+        self
+    }
+
+    fn border_style(&self) -> Style {
+        if self.selected {
+            Style::default()
+                .fg(self.palette.accent)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            self.palette.border()
+        }
+    }
+
+    fn block(&self) -> Block<'static> {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(self.border_style())
+    }
+
+    fn back_lines(&self, width: u16, height: u16) -> Vec<Spans<'static>> {
+        let pattern = self.back.pattern().chars().collect::<Vec<_>>();
+        let style = Style::default().fg(self.palette.card_back);
+        (0..height)
+            .map(|row| {
+                let line = (0..width)
+                    .map(|col| match pattern.len() {
+                        0 => ' ',
+                        n => pattern[(row + col) as usize % n],
+                    })
+                    .collect::<String>();
+                Spans::from(Span::styled(line, style))
+            })
+            .collect()
+    }
+
+    fn face_style(&self, tile: &AnyTile) -> Style {
+        let color = match tile {
+            AnyTile::Card(card) => self.palette.suit(card.color()),
+            AnyTile::Symbol(_) => self.palette.accent,
+            AnyTile::Paired(tile) => tile
+                .color
+                .as_deref()
+                .and_then(theme::parse_color)
+                .unwrap_or(self.palette.accent),
+        };
+        Style::default().fg(color).add_modifier(Modifier::BOLD)
+    }
+
+    fn face_lines(&self, card: &Card, width: u16) -> Vec<Spans<'static>> {
+        let style = Style::default()
+            .fg(self.palette.suit(card.color()))
+            .add_modifier(Modifier::BOLD);
+        let corner = format!("{}{}", card.rank(), card.suit());
+        let pad = (width as usize).saturating_sub(corner.chars().count());
+        let mut lines = vec![Spans::from(Span::styled(corner.clone(), style))];
+        lines.extend(pip(card).iter().map(|row| {
+            Spans::from(Span::styled(
+                format!("{row:^w$}", w = width as usize),
+                style,
+            ))
+        }));
+        lines.push(Spans::from(Span::styled(
+            format!("{}{corner}", " ".repeat(pad)),
+            style,
+        )));
+        lines
+    }
+
+    /// A symbol face: just the symbol, in the middle of the tile.
+    fn symbol_lines(&self, tile: &AnyTile, height: u16) -> Vec<Spans<'static>> {
+        let mut lines = vec![Spans::default(); (height / 2) as usize];
+        lines.push(Spans::from(Span::styled(
+            tile.to_string(),
+            self.face_style(tile),
+        )));
+        lines
+    }
+
+    /// A tile too small for the full face: the card as three characters, in a
+    /// border if there's room for one.
+    fn render_compact(&self, card: &CardProxy, face_up: bool, area: Rect, buf: &mut Buffer) {
+        let (mut text, mut style) = if face_up {
+            (card.card().to_string(), self.face_style(card.card()))
+        } else {
+            (
+                self.back.pattern().to_owned(),
+                Style::default().fg(self.palette.card_back),
+            )
+        };
+        if area.height < 3 {
+            if self.selected {
+                text = format!("{text:^3}");
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            Paragraph::new(Span::styled(text, style))
+                .alignment(Alignment::Center)
+                .render(area, buf);
+            return;
+        }
+        Paragraph::new(Span::styled(text, style))
+            .alignment(Alignment::Center)
+            .block(self.block())
+            .render(area, buf);
+    }
+}
+
+impl Widget for CardTile<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Some(card) = self.card else {
+            if self.selected && area.height >= 3 {
+                self.block().render(area, buf);
+            } else if self.selected {
+                let style = Style::default().add_modifier(Modifier::REVERSED);
+                Paragraph::new(Span::styled("   ", style))
+                    .alignment(Alignment::Center)
+                    .render(area, buf);
+            }
+            return;
+        };
+
+        // Turning over, the card narrows to its edge still showing the side
+        // it had, then widens again showing the other.
+        let mut face_up = !card.is_flipped();
+        let mut area = area;
+        if let Some(turned) = self.turned.filter(|&t| t < FLIP_TIME) {
+            let frame = (turned.as_millis() * FLIP_FRAMES as u128 / FLIP_TIME.as_millis()) as u32;
+            let half = FLIP_FRAMES / 2;
+            if frame < half {
+                face_up = !face_up;
+            }
+            let width = area.width * frame.abs_diff(half) as u16 / half as u16;
+            if width < 2 {
+                let edge = Span::styled("┃", self.border_style());
+                let x = area.x + area.width / 2;
+                for y in area.top()..area.bottom() {
+                    buf.set_span(x, y, &edge, 1);
+                }
+                return;
+            }
+            area = Rect::new(
+                area.x + (area.width - width) / 2,
+                area.y,
+                width,
+                area.height,
+            );
+        }
+
+        if area.width < FULL_SIZE.0 || area.height < FULL_SIZE.1 {
+            self.render_compact(card, face_up, area, buf);
+            return;
+        }
+        let block = self.block();
+        let inner = block.inner(area);
+        block.render(area, buf);
+        match card.card() {
+            AnyTile::Card(face) if face_up => {
+                Paragraph::new(self.face_lines(face, inner.width)).render(inner, buf)
+            }
+            tile if face_up => Paragraph::new(self.symbol_lines(tile, inner.height))
+                .alignment(Alignment::Center)
+                .render(inner, buf),
+            _ => Paragraph::new(self.back_lines(inner.width, inner.height)).render(inner, buf),
+        }
+    }
+}
+
+/// The big suit symbol in the middle of a full card face.
+fn pip(card: &Card) -> [&'static str; 4] {
+    if card.rank() == Rank::Joker {
+        return [r" \|/ ", r" -*- ", r" /|\ ", r"     "];
+    }
+    match card.suit() {
+        Suit::Spades => [r"  .  ", r" /:\ ", r"(_:_)", r"  ^  "],
+        Suit::Hearts => [r" _ _ ", r"( v )", r" \ / ", r"  v  "],
+        Suit::Diamonds => [r"  ^  ", r" / \ ", r" \ / ", r"  v  "],
+        Suit::Clubs => [r"  _  ", r" (_) ", r"(_'_)", r"  |  "],
+    }
+}
@@ -0,0 +1,62 @@
+use std::time::{Duration, Instant};
+
+/// Wall-clock timing for a game in progress: total time, time spent on the
+/// current turn, and the split at each pair found.
+pub struct GameClock {
+    started: Instant,
+    base: Duration,
+    turn_started: Instant,
+    splits: Vec<Duration>,
+    stopped: Option<Duration>,
+}
+
+impl GameClock {
+    /// Starts the clock at `base`, the time already played before a game was
+    /// saved.
+    pub fn new(base: Duration) -> Self {
+        Self {
+            started: Instant::now(),
+            base,
+            turn_started: Instant::now(),
+            splits: vec![],
+            stopped: None,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.stopped
+            .unwrap_or_else(|| self.base + self.started.elapsed())
+    }
+
+    pub fn stop(&mut self) {
+        self.stopped = Some(self.elapsed());
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(stopped) = self.stopped.take() {
+            self.base = stopped;
+            self.started = Instant::now();
+        }
+    }
+
+    pub fn turn_elapsed(&self) -> Duration {
+        self.turn_started.elapsed()
+    }
+
+    pub fn new_turn(&mut self) {
+        self.turn_started = Instant::now();
+    }
+
+    pub fn split(&mut self) {
+        self.splits.push(self.elapsed());
+    }
+
+    /// Drops splits for pairs that were taken back.
+    pub fn truncate_splits(&mut self, pairs: usize) {
+        self.splits.truncate(pairs);
+    }
+
+    pub fn splits(&self) -> &[Duration] {
+        &self.splits
+    }
+}
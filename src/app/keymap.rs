@@ -0,0 +1,459 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::{collections::HashMap, fmt, fs, io, path::PathBuf};
+
+/// Something a key can be bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+    Edit,
+    Flip,
+    Undo,
+    Redo,
+    WatchReplay,
+    Pause,
+    Faster,
+    Slower,
+    First,
+    Last,
+    Jump,
+    Reset,
+    Help,
+    Quit,
+}
+
+/// Where an action can be used. Two actions may share a key as long as they
+/// are never used in the same context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Context {
+    Menu,
+    Game,
+    GameOver,
+    Replay,
+}
+
+impl Context {
+    pub const ALL: [Context; 4] = [
+        Context::Menu,
+        Context::Game,
+        Context::GameOver,
+        Context::Replay,
+    ];
+}
+
+impl Action {
+    pub const ALL: [Action; 20] = [
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::Confirm,
+        Action::Back,
+        Action::Edit,
+        Action::Flip,
+        Action::Undo,
+        Action::Redo,
+        Action::WatchReplay,
+        Action::Pause,
+        Action::Faster,
+        Action::Slower,
+        Action::First,
+        Action::Last,
+        Action::Jump,
+        Action::Reset,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    pub fn contexts(&self) -> &'static [Context] {
+        use Context::*;
+        match self {
+            Action::Up | Action::Down | Action::Help | Action::Quit => &Context::ALL,
+            Action::Left | Action::Right => &[Menu, Game, Replay],
+            Action::Back => &[Menu, Game, GameOver, Replay],
+            Action::Confirm => &[Menu, Game, GameOver],
+            Action::Edit | Action::Reset => &[Menu],
+            Action::Flip | Action::Undo | Action::Redo => &[Game],
+            Action::WatchReplay => &[GameOver],
+            Action::Pause
+            | Action::Faster
+            | Action::Slower
+            | Action::First
+            | Action::Last
+            | Action::Jump => &[Replay],
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Action::Up => "Move up",
+            Action::Down => "Move down",
+            Action::Left => "Move left",
+            Action::Right => "Move right",
+            Action::Confirm => "Confirm",
+            Action::Back => "Go back",
+            Action::Edit => "Start typing",
+            Action::Flip => "Turn over a card",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::WatchReplay => "Watch the replay",
+            Action::Pause => "Play/pause",
+            Action::Faster => "Faster",
+            Action::Slower => "Slower",
+            Action::First => "First move",
+            Action::Last => "Last move",
+            Action::Jump => "Jump to a move",
+            Action::Reset => "Reset to defaults",
+            Action::Help => "Show this help",
+            Action::Quit => "Quit",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    Arrows,
+    #[default]
+    Vim,
+    Wasd,
+}
+
+/// A key with the modifiers held along with it. Shift is left out for
+/// characters, since it's already in the character.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    const fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    const fn char(c: char) -> Self {
+        Self::new(KeyCode::Char(c))
+    }
+
+    pub fn from_event(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if !matches!(event.code, KeyCode::Char(_)) {
+            modifiers |= event.modifiers & KeyModifiers::SHIFT;
+        }
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+
+    /// Reads a key written like `q`, `enter`, `ctrl-z` or `f1`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            if lower.starts_with("ctrl-") {
+                modifiers |= KeyModifiers::CONTROL;
+            } else if lower.starts_with("alt-") {
+                modifiers |= KeyModifiers::ALT;
+            } else if lower.starts_with("shift-") {
+                modifiers |= KeyModifiers::SHIFT;
+            } else {
+                break;
+            }
+            rest = &rest[lower.find('-')? + 1..];
+        }
+        let mut chars = rest.chars();
+        let mut code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "space" => KeyCode::Char(' '),
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdown" => KeyCode::PageDown,
+                "insert" => KeyCode::Insert,
+                "delete" | "del" => KeyCode::Delete,
+                f => KeyCode::F(f.strip_prefix('f')?.parse().ok()?),
+            },
+        };
+        if let KeyCode::Char(c) = code {
+            if modifiers.contains(KeyModifiers::SHIFT) {
+                code = KeyCode::Char(c.to_ascii_uppercase());
+            }
+            modifiers -= KeyModifiers::SHIFT;
+        }
+        Some(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// The keymap file. Keys listed for an action replace the preset's keys for
+/// it.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct KeymapFile {
+    preset: Preset,
+    bindings: HashMap<Action, Vec<String>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<Key>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::preset(Preset::default())
+    }
+}
+
+impl Keymap {
+    pub fn preset(preset: Preset) -> Self {
+        use KeyCode::*;
+        let [up, down, left, right] = match preset {
+            Preset::Arrows => [None; 4],
+            Preset::Vim => ['k', 'j', 'h', 'l'].map(Some),
+            Preset::Wasd => ['w', 's', 'a', 'd'].map(Some),
+        };
+        let with = |code: KeyCode, c: Option<char>| {
+            [Some(Key::new(code)), c.map(Key::char)]
+                .into_iter()
+                .flatten()
+                .collect()
+        };
+        let bindings = Action::ALL.map(|action| {
+            let keys = match action {
+                Action::Up => with(Up, up),
+                Action::Down => with(Down, down),
+                Action::Left => with(Left, left),
+                Action::Right => with(Right, right),
+                Action::Confirm => vec![Key::new(Enter)],
+                Action::Back => vec![Key::char('q'), Key::new(Esc)],
+                Action::Edit => vec![Key::char('e')],
+                Action::Flip => vec![Key::char(' ')],
+                Action::Undo => vec![Key::char('u')],
+                Action::Redo | Action::WatchReplay | Action::Reset => vec![Key::char('r')],
+                Action::Pause => vec![Key::char(' ')],
+                Action::Faster => vec![Key::char('+'), Key::char('=')],
+                Action::Slower => vec![Key::char('-')],
+                Action::First => vec![Key::new(Home)],
+                Action::Last => vec![Key::new(End)],
+                Action::Jump => vec![Key::char('g')],
+                Action::Help => vec![Key::char('?')],
+                Action::Quit => vec![Key {
+                    code: Char('c'),
+                    modifiers: KeyModifiers::CONTROL,
+                }],
+            };
+            (action, keys)
+        });
+        Self {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("pairs-rs").join("keymap.toml"))
+    }
+
+    /// Reads the keymap file, falling back to the default preset when there
+    /// is none. Keys bound to two actions that can be used at the same time
+    /// are an error.
+    pub fn load() -> io::Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let s = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        Self::parse(&s)
+    }
+
+    /// Reads a keymap file's contents.
+    fn parse(s: &str) -> io::Result<Self> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let file: KeymapFile = toml::from_str(s).map_err(|e| invalid(e.to_string()))?;
+
+        let mut keymap = Self::preset(file.preset);
+        for (action, keys) in file.bindings {
+            let keys = keys
+                .iter()
+                .map(|k| Key::parse(k).ok_or_else(|| invalid(format!("unknown key \"{k}\""))))
+                .collect::<io::Result<_>>()?;
+            keymap.bindings.insert(action, keys);
+        }
+        if let Some((key, a, b)) = keymap.conflicts().first() {
+            return Err(invalid(format!(
+                "{key} is bound to both \"{a}\" and \"{b}\""
+            )));
+        }
+        Ok(keymap)
+    }
+
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Keys bound to two actions that share a context.
+    pub fn conflicts(&self) -> Vec<(Key, Action, Action)> {
+        let mut conflicts = vec![];
+        for (i, &a) in Action::ALL.iter().enumerate() {
+            for &b in &Action::ALL[i + 1..] {
+                if !a.contexts().iter().any(|c| b.contexts().contains(c)) {
+                    continue;
+                }
+                for &key in self.keys(a) {
+                    if self.keys(b).contains(&key) {
+                        conflicts.push((key, a, b));
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// The action `event` stands for in `context`, if any.
+    pub fn action(&self, event: KeyEvent, context: Context) -> Option<Action> {
+        let key = Key::from_event(event);
+        Action::ALL
+            .into_iter()
+            .filter(|a| a.contexts().contains(&context))
+            .find(|&a| self.keys(a).contains(&key))
+    }
+
+    /// The first key bound to `action`, for hints.
+    pub fn label(&self, action: Action) -> String {
+        self.keys(action)
+            .first()
+            .map_or_else(|| "(unbound)".to_owned(), Key::to_string)
+    }
+
+    /// Every key bound to `action`, for the help overlay.
+    pub fn labels(&self, action: Action) -> String {
+        self.keys(action)
+            .iter()
+            .map(Key::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        Key { code, modifiers }
+    }
+
+    #[test]
+    fn parses_keys_with_modifiers() {
+        assert_eq!(
+            Key::parse("ctrl-z"),
+            Some(key(KeyCode::Char('z'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            Key::parse("Ctrl-Alt-Enter").unwrap().to_string(),
+            "Ctrl-Alt-Enter"
+        );
+        assert_eq!(Key::parse("f5"), Some(Key::new(KeyCode::F(5))));
+        assert_eq!(
+            Key::parse("shift-tab"),
+            Some(key(KeyCode::Tab, KeyModifiers::SHIFT))
+        );
+    }
+
+    #[test]
+    fn shift_uppercases_characters() {
+        assert_eq!(Key::parse("shift-a"), Some(Key::char('A')));
+        assert_eq!(Key::parse("shift-a"), Key::parse("A"));
+        let typed = KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT);
+        assert_eq!(Key::parse("shift-a"), Some(Key::from_event(typed)));
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        for s in ["", "hyper-a", "enterr", "fx", "f99999", "ctrl-"] {
+            assert_eq!(Key::parse(s), None, "{s:?}");
+        }
+        let err = Keymap::parse("[bindings]\nundo = [\"nope\"]").unwrap_err();
+        assert_eq!(err.to_string(), "unknown key \"nope\"");
+    }
+
+    #[test]
+    fn keys_may_be_shared_across_contexts_only() {
+        // Editing is only for menus and flipping only for the game.
+        let keymap = Keymap::parse("[bindings]\nedit = [\"x\"]\nflip = [\"x\"]").unwrap();
+        assert_eq!(keymap.keys(Action::Edit), [Key::char('x')]);
+        let x = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(keymap.action(x, Context::Menu), Some(Action::Edit));
+        assert_eq!(keymap.action(x, Context::Game), Some(Action::Flip));
+
+        let err = Keymap::parse("[bindings]\nundo = [\"x\"]\nflip = [\"x\"]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "x is bound to both \"Turn over a card\" and \"Undo\""
+        );
+    }
+
+    #[test]
+    fn presets_have_no_conflicts() {
+        for preset in [Preset::Arrows, Preset::Vim, Preset::Wasd] {
+            assert_eq!(Keymap::preset(preset).conflicts(), vec![], "{preset:?}");
+        }
+        let keymap = Keymap::parse("preset = \"wasd\"").unwrap();
+        assert_eq!(
+            keymap.keys(Action::Up),
+            [Key::new(KeyCode::Up), Key::char('w')]
+        );
+        assert_eq!(Keymap::default().label(Action::Left), "←");
+        assert_eq!(Keymap::preset(Preset::Vim).labels(Action::Left), "←, h");
+    }
+}
@@ -1,13 +1,46 @@
+pub mod card;
+pub mod clock;
+pub mod keymap;
+pub mod save;
+pub mod screens;
+pub mod settings;
 pub mod state;
+pub mod stats;
+pub mod theme;
+pub mod tiles;
 pub mod ui;
+pub mod viewer;
 
-use crossterm::event::{self, Event, KeyCode};
+use crate::{
+    core::{
+        ai::{Bot, Difficulty},
+        game::{Game, Phase, Pos, Reveal},
+        replay::Replay,
+        Player,
+    },
+    net::{self, Client, ClientEvent, Host, HostRequest},
+};
+use clock::GameClock;
+use crossterm::event::{self, Event, MouseButton, MouseEvent, MouseEventKind};
+use keymap::{Action, Context, Keymap};
+use save::SaveFile;
+use settings::{Settings, TimerMode};
 use state::{InputMode, PopupMsg, Screen, TitleButtons};
-use std::io;
-use tui::{backend::Backend, Terminal};
+use stats::{GameRecord, Stats};
+use std::{
+    collections::HashMap,
+    io,
+    time::{Duration, Instant},
+};
+use theme::Palette;
+use tui::{backend::Backend, layout::Rect, Terminal};
+use viewer::ReplayViewer;
 
-pub struct UiDefaults<const N: usize = 3> {
+pub struct UiDefaults<const N: usize = 7> {
     all_buttons: [TitleButtons; N],
+    player_count_bounds: (u8, u8),
+    bot_move_delay: Duration,
+    tick_rate: Duration,
 }
 
 impl Default for UiDefaults {
@@ -15,23 +48,46 @@ impl Default for UiDefaults {
         Self {
             all_buttons: [
                 TitleButtons::Start,
+                TitleButtons::Host,
+                TitleButtons::Join,
+                TitleButtons::Continue,
+                TitleButtons::Leaderboard,
                 TitleButtons::Options,
                 TitleButtons::Exit,
             ],
+            player_count_bounds: (1, 8),
+            bot_move_delay: Duration::from_millis(600),
+            tick_rate: Duration::from_millis(50),
         }
     }
 }
 
+#[derive(Default)]
 pub struct UiVar {
+    player_count: u8,
     player_names: Vec<String>,
+    player_bots: Vec<Option<Difficulty>>,
+    next_bot: Option<Difficulty>,
+    cursor: Pos,
+    mismatch_at: Option<Instant>,
+    last_move: Option<Instant>,
+    hosting: bool,
+    term_size: Rect,
+    faces: Vec<Vec<Option<bool>>>,
+    turned: HashMap<Pos, Instant>,
+    help: bool,
 }
 
-impl Default for UiVar {
-    fn default() -> Self {
-        Self {
-            player_names: vec![],
-        }
-    }
+/// A screen under the current one, with the input and selection it had.
+struct Covered {
+    screen: Screen,
+    input: String,
+    index: i8,
+}
+
+pub enum Link {
+    Host(Host),
+    Client(Client),
 }
 
 pub struct App {
@@ -41,100 +97,767 @@ pub struct App {
     curr_index: i8,
     curr_index_mod: usize,
     curr_screen: Screen,
+    covered: Vec<Covered>,
     ui_var: UiVar,
     ui_defaults: UiDefaults,
+    settings: Settings,
+    game: Option<Game>,
+    /// Settings the running game is played under: the ones it was dealt or
+    /// saved with.
+    game_settings: Settings,
+    /// When the running game was last saved, so finishing it clears that save
+    /// and no other.
+    saved_at: Option<u64>,
+    bots: Vec<Option<Bot>>,
+    link: Option<Link>,
+    replay: Option<Replay>,
+    viewer: Option<ReplayViewer>,
+    stats: Option<Stats>,
+    clock: Option<GameClock>,
+    palette: Palette,
+    keymap: Keymap,
 }
 
-impl Default for App {
-    fn default() -> App {
-        App {
+impl App {
+    pub fn new(settings: Settings) -> App {
+        let mut app = App {
             input: String::new(),
             input_mode: InputMode::Normal,
             popup: None,
             curr_index: 0,
             curr_index_mod: 1,
             curr_screen: Screen::Title,
+            covered: vec![],
             ui_var: UiVar::default(),
             ui_defaults: UiDefaults::default(),
+            game: None,
+            game_settings: settings.clone(),
+            saved_at: None,
+            bots: vec![],
+            link: None,
+            replay: None,
+            viewer: None,
+            stats: None,
+            clock: None,
+            palette: Palette::built_in(theme::BUILT_IN[0], settings.accent).unwrap(),
+            keymap: Keymap::default(),
+            settings,
+        };
+        app.load_palette();
+        app.load_keymap();
+        app
+    }
+
+    /// Picks up the theme and accent color from the settings, keeping the
+    /// dark theme if the theme can't be loaded.
+    fn load_palette(&mut self) {
+        let accent = self.settings.accent;
+        match Palette::load(&self.settings.theme, accent) {
+            Ok(palette) => self.palette = palette,
+            Err(err) => {
+                self.palette = Palette::built_in(theme::BUILT_IN[0], accent).unwrap();
+                self.popup = Some(PopupMsg::err(format!("Failed to load theme: {err}")));
+            }
+        }
+    }
+
+    /// Points out a tile set that can't be dealt from as soon as it's picked.
+    fn check_tiles(&mut self) {
+        if let Err(err) = tiles::load(&self.settings.tiles) {
+            self.popup = Some(PopupMsg::err(format!("Failed to load tiles: {err}")));
         }
     }
+
+    /// Reads the keymap file, keeping the default keys if it can't be used.
+    fn load_keymap(&mut self) {
+        match Keymap::load() {
+            Ok(keymap) => self.keymap = keymap,
+            Err(err) => {
+                self.popup = Some(PopupMsg::err(format!("Failed to load key bindings: {err}")));
+            }
+        }
+    }
+
+    /// Which of the keymap's contexts the keys are read in.
+    fn context(&self) -> Context {
+        self.curr_screen.handler().context(self)
+    }
+
+    /// Opens `screen` over the current one, which comes back as it was when
+    /// `screen` is popped.
+    fn push_screen(&mut self, screen: Screen) {
+        self.covered.push(Covered {
+            screen: std::mem::replace(&mut self.curr_screen, screen),
+            input: std::mem::take(&mut self.input),
+            index: self.curr_index,
+        });
+        self.curr_index = 0;
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Goes back to the screen under the current one. The title screen is
+    /// never popped.
+    fn pop_screen(&mut self) {
+        let Some(covered) = self.covered.pop() else {
+            return;
+        };
+        self.curr_screen = covered.screen;
+        self.input = covered.input;
+        self.curr_index = covered.index;
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Swaps the current screen for `screen`, so going back skips it.
+    fn replace_screen(&mut self, screen: Screen) {
+        self.curr_screen = screen;
+        self.input.clear();
+        self.curr_index = 0;
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Goes all the way back to the title screen.
+    fn pop_to_title(&mut self) {
+        while !self.covered.is_empty() {
+            self.pop_screen();
+        }
+    }
+
+    pub fn show_popup(&mut self, popup: PopupMsg) {
+        self.popup = Some(popup);
+    }
+
+    /// Call with the terminal size at startup and whenever it changes.
+    pub fn resize(&mut self, size: Rect) {
+        self.ui_var.term_size = size;
+    }
+
+    fn start_game(&mut self) {
+        let rule = self.settings.pair_rule;
+        let deck = match self.settings.paired_deck() {
+            Ok(deck) => deck,
+            Err(err) => {
+                self.popup = Some(PopupMsg::err(format!("Failed to load tiles: {err}")));
+                return;
+            }
+        };
+        let players = self
+            .ui_var
+            .player_names
+            .iter()
+            .zip(&self.ui_var.player_bots)
+            .map(|(name, bot)| match bot {
+                Some(difficulty) => Player::bot(name.clone(), *difficulty),
+                None => Player::new(name.clone()),
+            })
+            .collect();
+        match Game::new(
+            &deck,
+            self.settings.board_size,
+            players,
+            rule,
+            self.settings.seed_mode.seed(),
+        ) {
+            Ok(game) => {
+                if self.ui_var.hosting {
+                    match Host::bind(net::DEFAULT_PORT) {
+                        Ok(host) => self.link = Some(Link::Host(host)),
+                        Err(err) => {
+                            self.popup = Some(PopupMsg::err(format!(
+                                "Failed to host on port {}: {err}",
+                                net::DEFAULT_PORT
+                            )));
+                            return;
+                        }
+                    }
+                }
+                self.enter_game(game)
+            }
+            Err(err) => self.popup = Some(PopupMsg::err(err.to_string())),
+        }
+    }
+
+    /// Connects to a hosted game and waits for its state on the gameplay
+    /// screen. `addr` may leave out the port.
+    fn join_game(&mut self, name: &str, addr: &str) {
+        match Client::connect(addr, name) {
+            Ok(client) => {
+                self.link = Some(Link::Client(client));
+                self.game = None;
+                self.game_settings = self.settings.clone();
+                self.replace_screen(Screen::Gameplay);
+            }
+            Err(err) => self.popup = Some(PopupMsg::err(format!("Failed to join {addr}: {err}"))),
+        }
+    }
+
+    /// Starts playing `game`, freshly dealt or continued from a save. Cards
+    /// keep the positions they were dealt at; only the screen wraps them to
+    /// fit the terminal.
+    pub fn enter_game(&mut self, game: Game) {
+        self.bots = game
+            .players()
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                p.bot
+                    .map(|d| Bot::new(d, game.seed().wrapping_add(i as u64)))
+            })
+            .collect();
+        self.replay = Some(Replay::new(&game));
+        self.clock = Some(GameClock::new(Duration::ZERO));
+        self.game = Some(game);
+        self.game_settings = self.settings.clone();
+        self.saved_at = None;
+        self.ui_var.cursor = (0, 0);
+        self.ui_var.mismatch_at = None;
+        self.ui_var.last_move = Some(Instant::now());
+        self.pop_to_title();
+        self.push_screen(Screen::Gameplay);
+        self.observe();
+    }
+
+    fn continue_game(&mut self) {
+        match SaveFile::load() {
+            Ok(Some(save)) => {
+                self.enter_game(save.game);
+                self.game_settings = save.settings;
+                self.saved_at = Some(save.saved_at);
+                if let Some(mut replay) = save.replay {
+                    replay.resume();
+                    self.clock = Some(GameClock::new(replay.elapsed()));
+                    self.replay = Some(replay);
+                }
+            }
+            Ok(None) => self.popup = Some(PopupMsg::info("No saved game found.".into())),
+            Err(err) => {
+                self.popup = Some(PopupMsg::err(format!("Failed to load saved game: {err}")))
+            }
+        }
+    }
+
+    /// Saves an unfinished game so it can be continued later, or clears the
+    /// save once the game is over.
+    fn save_game(&mut self) {
+        let Some(game) = &self.game else { return };
+        if let Some(Link::Client(_)) = self.link {
+            return;
+        }
+        if game.is_over() {
+            self.finish_game();
+            return;
+        }
+        let save = SaveFile::new(
+            self.game_settings.clone(),
+            game.clone(),
+            self.replay.clone(),
+        );
+        self.popup = Some(match save.save() {
+            Ok(()) => {
+                self.saved_at = Some(save.saved_at);
+                PopupMsg::info("Game saved. Choose Continue to resume it.".into())
+            }
+            Err(err) => PopupMsg::err(format!("Failed to save game: {err}")),
+        });
+    }
+
+    /// Keeps the stats and replay of a finished game, and clears its save if
+    /// it was continued from one. Each step is tried even if another fails.
+    fn finish_game(&mut self) {
+        let Some(game) = &self.game else { return };
+        let mut popup = self
+            .replay
+            .as_ref()
+            .map(|replay| match save::save_replay(replay) {
+                Ok(path) => PopupMsg::info(format!("Replay saved to {}.", path.display())),
+                Err(err) => PopupMsg::err(format!("Failed to save replay: {err}")),
+            });
+        let time = self.replay.as_ref().map_or(Duration::ZERO, Replay::elapsed);
+        if let Err(err) = Stats::record(GameRecord::new(game, time)) {
+            popup = Some(PopupMsg::err(format!("Failed to record stats: {err}")));
+        }
+        if let Some(saved_at) = self.saved_at.take() {
+            if let Err(err) = SaveFile::delete_if_saved_at(saved_at) {
+                popup = Some(PopupMsg::err(format!("Failed to clear saved game: {err}")));
+            }
+        }
+        if popup.is_some() {
+            self.popup = popup;
+        }
+    }
+
+    pub fn show_leaderboard(&mut self) {
+        match Stats::load() {
+            Ok(stats) => {
+                self.stats = Some(stats);
+                self.push_screen(Screen::Leaderboard);
+            }
+            Err(err) => self.popup = Some(PopupMsg::err(format!("Failed to load stats: {err}"))),
+        }
+    }
+
+    fn quit_game(&mut self) {
+        self.game = None;
+        self.bots.clear();
+        self.link = None;
+        self.replay = None;
+        self.viewer = None;
+        self.clock = None;
+        self.saved_at = None;
+        self.ui_var.hosting = false;
+        self.ui_var.player_count = 0;
+        self.ui_var.player_names.clear();
+        self.ui_var.player_bots.clear();
+        self.pop_to_title();
+    }
+
+    fn observe(&mut self) {
+        let Some(game) = &self.game else { return };
+        for bot in self.bots.iter_mut().flatten() {
+            bot.observe(game);
+        }
+    }
+
+    pub fn watch_replay(&mut self, replay: Replay) {
+        self.viewer = Some(ReplayViewer::new(replay));
+        self.push_screen(Screen::Replay);
+    }
+
+    fn leave_replay(&mut self) {
+        self.viewer = None;
+        self.pop_screen();
+    }
+
+    fn report_send(&mut self, res: io::Result<()>) {
+        if let Err(err) = res {
+            self.popup = Some(PopupMsg::err(format!("Failed to reach host: {err}")));
+        }
+    }
+
+    fn reveal(&mut self, pos: Pos) {
+        if let Some(Link::Client(client)) = &mut self.link {
+            let res = client.reveal(pos);
+            self.report_send(res);
+            return;
+        }
+        let Some(game) = self.game.as_mut() else {
+            return;
+        };
+        let res = match game.reveal(pos) {
+            Ok(res) => res,
+            Err(_) => return,
+        };
+        match res {
+            Reveal::Mismatch(..) => self.ui_var.mismatch_at = Some(Instant::now()),
+            Reveal::Match(..) => {
+                if let Some(clock) = &mut self.clock {
+                    clock.split();
+                    clock.new_turn();
+                    if game.is_over() {
+                        clock.stop();
+                    }
+                }
+            }
+            Reveal::First(_) => {}
+        }
+        if let Some(replay) = &mut self.replay {
+            replay.record(game, &res);
+        }
+        self.ui_var.last_move = Some(Instant::now());
+        self.observe();
+    }
+
+    fn flip_back(&mut self) {
+        if let Some(Link::Client(client)) = &mut self.link {
+            let res = client.flip_back();
+            self.report_send(res);
+            return;
+        }
+        let Some(game) = self.game.as_mut() else {
+            return;
+        };
+        if game.end_turn().is_ok() {
+            if let Some(replay) = &mut self.replay {
+                replay.record_turn_change(game);
+            }
+            if let Some(clock) = &mut self.clock {
+                clock.new_turn();
+            }
+            self.ui_var.mismatch_at = None;
+            self.ui_var.last_move = Some(Instant::now());
+            self.observe();
+        }
+    }
+
+    /// Steps back (or forward) through a practice game's history. A mismatch
+    /// is skipped over rather than stopped at, since it would flip straight
+    /// back on the next key press.
+    fn step_history(&mut self, redo: bool) {
+        let Some(game) = self.game.as_mut() else {
+            return;
+        };
+        if self.link.is_some() {
+            self.popup = Some(PopupMsg::warn(
+                "Undo is not available in networked games.".into(),
+            ));
+            return;
+        }
+        let step = |game: &mut Game| if redo { game.redo() } else { game.undo() };
+        if let Err(err) = step(game) {
+            self.popup = Some(PopupMsg::warn(err.to_string()));
+            return;
+        }
+        let mut steps = 1;
+        if let Phase::Mismatch(..) = game.phase() {
+            if step(game).is_ok() {
+                steps += 1;
+            }
+        }
+        self.ui_var.mismatch_at = match game.phase() {
+            Phase::Mismatch(..) => Some(Instant::now()),
+            _ => None,
+        };
+        if let Some(clock) = &mut self.clock {
+            clock.truncate_splits(game.players()[0].pairs());
+            clock.new_turn();
+            if !game.is_over() {
+                clock.resume();
+            }
+        }
+        if let Some(replay) = &mut self.replay {
+            for _ in 0..steps {
+                if redo {
+                    replay.record_redo();
+                } else {
+                    replay.record_undo();
+                }
+            }
+        }
+    }
+
+    fn can_flip_back(&self) -> bool {
+        let elapsed = self
+            .ui_var
+            .mismatch_at
+            .map_or(Duration::MAX, |t| t.elapsed());
+        elapsed >= self.game_settings.flip_back_delay()
+    }
+
+    /// Whether the player on turn is played from this terminal.
+    fn is_local_turn(&self) -> bool {
+        let Some(game) = &self.game else { return false };
+        match &self.link {
+            _ if game.current_player().is_bot() => false,
+            Some(Link::Host(host)) => !host.is_remote(game.turn()),
+            Some(Link::Client(client)) => client.player() == Some(game.turn()),
+            None => true,
+        }
+    }
+
+    /// How long until the bot on turn should move.
+    fn bot_wait(&self) -> Option<Duration> {
+        let game = self.game.as_ref()?;
+        if !matches!(self.curr_screen, Screen::Gameplay)
+            || matches!(self.link, Some(Link::Client(_)))
+            || game.is_over()
+            || !game.current_player().is_bot()
+        {
+            return None;
+        }
+        let delay = match game.phase() {
+            Phase::Mismatch(..) => self.game_settings.flip_back_delay(),
+            _ => self.ui_defaults.bot_move_delay,
+        };
+        let elapsed = self.ui_var.last_move.map_or(Duration::MAX, |t| t.elapsed());
+        Some(delay.saturating_sub(elapsed))
+    }
+
+    fn step_bot(&mut self) {
+        let Some(game) = &self.game else { return };
+        if let Phase::Mismatch(..) = game.phase() {
+            self.flip_back();
+            return;
+        }
+        let choice = self
+            .bots
+            .get_mut(game.turn())
+            .and_then(Option::as_mut)
+            .and_then(|bot| bot.choose(game));
+        if let Some(pos) = choice {
+            self.reveal(pos);
+        }
+    }
+
+    /// How long the event loop may block waiting for input.
+    fn tick_timeout(&self) -> Duration {
+        let viewer = self.viewer.as_ref().and_then(|v| v.wait());
+        [self.bot_wait(), viewer]
+            .into_iter()
+            .flatten()
+            .fold(self.ui_defaults.tick_rate, Duration::min)
+    }
+
+    /// Ends the game or the current turn when the timer mode says time is up.
+    fn check_timers(&mut self) {
+        if let Some(Link::Client(_)) = self.link {
+            return;
+        }
+        let (Some(game), Some(clock)) = (self.game.as_mut(), self.clock.as_mut()) else {
+            return;
+        };
+        if game.is_over() {
+            return;
+        }
+        match self.game_settings.timer {
+            TimerMode::Countdown if clock.elapsed() >= self.game_settings.time_limit() => {
+                game.time_up();
+                clock.stop();
+                if let Some(replay) = &mut self.replay {
+                    replay.record_time_up();
+                }
+                self.ui_var.mismatch_at = None;
+                self.popup = Some(PopupMsg::info("Time's up!".into()));
+            }
+            TimerMode::TurnLimit if clock.turn_elapsed() >= self.game_settings.turn_limit() => {
+                let player = game.turn();
+                if game.forfeit_turn().is_err() {
+                    return;
+                }
+                clock.new_turn();
+                if let Some(replay) = &mut self.replay {
+                    replay.record_forfeit(player);
+                }
+                self.ui_var.mismatch_at = None;
+                self.ui_var.last_move = Some(Instant::now());
+            }
+            _ => return,
+        }
+        self.observe();
+    }
+
+    fn tick(&mut self) {
+        self.check_timers();
+        self.pump_network();
+        if self.bot_wait() == Some(Duration::ZERO) {
+            self.step_bot();
+        }
+        if let Some(viewer) = &mut self.viewer {
+            if viewer.wait() == Some(Duration::ZERO) {
+                viewer.forward();
+            }
+        }
+        self.track_flips();
+    }
+
+    /// The game on screen, live or replayed.
+    fn shown_game(&self) -> Option<&Game> {
+        match self.curr_screen {
+            Screen::Gameplay => self.game.as_ref(),
+            Screen::Replay => self.viewer.as_ref().map(|v| v.game()),
+            _ => None,
+        }
+    }
+
+    /// Notes the cards turned over since the last tick, so they can be drawn
+    /// mid-flip for a moment.
+    fn track_flips(&mut self) {
+        let faces = self.shown_game().map_or(vec![], |game| {
+            game.board()
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|c| c.as_ref().map(|c| c.is_flipped()))
+                        .collect()
+                })
+                .collect()
+        });
+        let old = std::mem::replace(&mut self.ui_var.faces, faces);
+        let same_shape = old.len() == self.ui_var.faces.len()
+            && old
+                .iter()
+                .zip(&self.ui_var.faces)
+                .all(|(a, b)| a.len() == b.len());
+        if !same_shape {
+            self.ui_var.turned.clear();
+            return;
+        }
+        for (x, (old, new)) in old.iter().zip(&self.ui_var.faces).enumerate() {
+            for (y, (old, new)) in old.iter().zip(new).enumerate() {
+                if let (Some(a), Some(b)) = (old, new) {
+                    if a != b {
+                        self.ui_var
+                            .turned
+                            .insert((x as u8, y as u8), Instant::now());
+                    }
+                }
+            }
+        }
+        self.ui_var
+            .turned
+            .retain(|_, t| t.elapsed() < card::FLIP_TIME);
+    }
+
+    /// How long ago the card at `pos` was turned over, while it's still
+    /// turning.
+    fn turned(&self, pos: Pos) -> Option<Duration> {
+        self.ui_var.turned.get(&pos).map(|t| t.elapsed())
+    }
+
+    fn pump_network(&mut self) {
+        match &mut self.link {
+            Some(Link::Host(host)) => {
+                let Some(game) = &self.game else { return };
+                for request in host.poll(game) {
+                    self.handle_request(request);
+                }
+                if let (Some(Link::Host(host)), Some(game)) = (&mut self.link, &self.game) {
+                    host.broadcast(game);
+                }
+            }
+            Some(Link::Client(client)) => {
+                for event in client.poll() {
+                    match event {
+                        ClientEvent::Welcome(_) => {}
+                        ClientEvent::State(game) => self.sync_game(game),
+                        ClientEvent::Error(msg) => {
+                            self.quit_game();
+                            self.popup = Some(PopupMsg::err(msg));
+                            return;
+                        }
+                        ClientEvent::Disconnected => {
+                            let over = self.game.as_ref().is_some_and(|g| g.is_over());
+                            self.quit_game();
+                            self.popup = Some(if over {
+                                PopupMsg::info("The host closed the game.".into())
+                            } else {
+                                PopupMsg::err("Lost connection to the host.".into())
+                            });
+                            return;
+                        }
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Applies a move a remote player asked for, if it's their turn.
+    fn handle_request(&mut self, request: HostRequest) {
+        let Some(game) = &self.game else { return };
+        match request {
+            HostRequest::Reveal { player, pos } if player == game.turn() => self.reveal(pos),
+            HostRequest::FlipBack { player } if player == game.turn() && self.can_flip_back() => {
+                self.flip_back()
+            }
+            _ => {}
+        }
+    }
+
+    /// Replaces the local copy of a hosted game with the host's latest state.
+    fn sync_game(&mut self, game: Game) {
+        let was_mismatch = matches!(
+            self.game.as_ref().map(|g| g.phase()),
+            Some(Phase::Mismatch(..))
+        );
+        match game.phase() {
+            Phase::Mismatch(..) if !was_mismatch => self.ui_var.mismatch_at = Some(Instant::now()),
+            Phase::Mismatch(..) => {}
+            _ => self.ui_var.mismatch_at = None,
+        }
+        if self.game.is_none() {
+            self.ui_var.cursor = (0, 0);
+        }
+        self.game = Some(game);
+    }
+
+    /// Acts on the selected title button. Returns false for Exit.
+    fn press_title_button(&mut self) -> bool {
+        match self.ui_defaults.all_buttons[self.curr_index as usize] {
+            TitleButtons::Start => {
+                self.ui_var.hosting = false;
+                self.push_screen(Screen::PlayerCountInput);
+            }
+            TitleButtons::Host => {
+                self.ui_var.hosting = true;
+                self.push_screen(Screen::PlayerCountInput);
+            }
+            TitleButtons::Join => self.push_screen(Screen::JoinInput),
+            TitleButtons::Continue => self.continue_game(),
+            TitleButtons::Leaderboard => self.show_leaderboard(),
+            TitleButtons::Options => self.push_screen(Screen::Options),
+            TitleButtons::Exit => return false,
+        }
+        true
+    }
+
+    /// Hovering selects a title button or card, clicking presses or flips it.
+    /// Hit-tests against the terminal laid out the same way [`ui::ui`] does.
+    /// Returns false when the click asks to exit.
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> bool {
+        let area = self.ui_var.term_size;
+        let clicked = matches!(mouse.kind, MouseEventKind::Down(MouseButton::Left));
+        if !clicked && !matches!(mouse.kind, MouseEventKind::Moved) || !ui::fits(area) {
+            return true;
+        }
+        if clicked && self.popup.take().is_some() {
+            return true;
+        }
+        self.curr_screen.handler().on_mouse(self, mouse, clicked)
+    }
+
+    fn move_cursor(&mut self, dx: i8, dy: i8) {
+        let Some(game) = &self.game else { return };
+        let Some(view) = ui::board_view(self.ui_var.term_size, game) else {
+            return;
+        };
+        self.ui_var.cursor = view.step(self.ui_var.cursor, dx, dy);
+    }
 }
 
 pub fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
     loop {
+        app.tick();
         terminal.draw(|f| ui::ui(f, &app))?;
 
-        if let Event::Key(key) = event::read()? {
-            if let Some(_) = app.popup {
+        if !event::poll(app.tick_timeout())? {
+            continue;
+        }
+
+        let event = event::read()?;
+        if let Event::Resize(width, height) = event {
+            app.resize(Rect::new(0, 0, width, height));
+            continue;
+        }
+        if let Event::Mouse(mouse) = event {
+            if !app.handle_mouse(mouse) {
+                return Ok(());
+            }
+            continue;
+        }
+        if let Event::Key(key) = event {
+            if app.popup.is_some() {
                 app.popup = None
             }
-            match app.curr_screen {
-                Screen::Title => {
-                    app.curr_index_mod = app.ui_defaults.all_buttons.len();
-                    let mod_curr_index = |app: &mut App| {
-                        app.curr_index = app.curr_index.rem_euclid(app.curr_index_mod as i8)
-                    };
-                    match key.code {
-                        KeyCode::Down | KeyCode::Right => {
-                            app.curr_index += 1;
-                            mod_curr_index(&mut app);
-                        }
-                        KeyCode::Up | KeyCode::Left => {
-                            app.curr_index -= 1;
-                            mod_curr_index(&mut app);
-                        }
-                        KeyCode::Enter => {
-                            match app.ui_defaults.all_buttons[app.curr_index as usize] {
-                                TitleButtons::Start => {
-                                    app.curr_screen = Screen::PlayerCountInput;
-                                }
-                                TitleButtons::Options => app.curr_screen = Screen::Options,
-                                TitleButtons::Exit => return Ok(()),
-                            }
-                        }
-                        _ => {}
-                    }
+            if app.ui_var.help {
+                app.ui_var.help = false;
+                continue;
+            }
+            let action = match app.input_mode {
+                InputMode::Normal => app.keymap.action(key, app.context()),
+                InputMode::Action1 => None,
+            };
+            match action {
+                Some(Action::Help) => {
+                    app.ui_var.help = true;
+                    continue;
                 }
-                Screen::PlayerCountInput | Screen::PlayerNameInput => match app.input_mode {
-                    InputMode::Normal => match key.code {
-                        KeyCode::Char('e') => {
-                            app.input_mode = InputMode::Action1;
-                        }
-                        KeyCode::Char('q') => {
-                            return Ok(());
-                        }
-                        _ => {}
-                    },
-                    InputMode::Action1 => match key.code {
-                        KeyCode::Enter => {
-                            app.input = app.input.trim().into();
-                            if app.input.is_empty() {
-                                app.popup = Some(PopupMsg::warn("Name is required.".into()));
-                                continue;
-                            }
-                            if app.ui_var.player_names.contains(&app.input) {
-                                app.popup = Some(PopupMsg::warn(format!(
-                                    "Name \"{}\" already exists.",
-                                    &app.input
-                                )));
-                                continue;
-                            }
-                            app.ui_var.player_names.push(app.input.drain(..).collect());
-                        }
-                        KeyCode::Char(c) => {
-                            app.input.push(c);
-                        }
-                        KeyCode::Backspace => {
-                            app.input.pop();
-                        }
-                        KeyCode::Esc => {
-                            app.input_mode = InputMode::Normal;
-                        }
-                        _ => {}
-                    },
-                },
-                Screen::Gameplay => todo!(),
-                Screen::Options => todo!(),
+                Some(Action::Quit) => {
+                    app.save_game();
+                    return Ok(());
+                }
+                _ => {}
+            }
+            if !app.curr_screen.handler().on_key(&mut app, key, action) {
+                return Ok(());
             }
         }
     }
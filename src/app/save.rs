@@ -0,0 +1,124 @@
+use super::settings::Settings;
+use crate::core::{game::Game, replay::Replay};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SaveFile {
+    pub version: u32,
+    pub saved_at: u64,
+    pub settings: Settings,
+    pub game: Game,
+    #[serde(default)]
+    pub replay: Option<Replay>,
+}
+
+impl SaveFile {
+    pub const VERSION: u32 = 1;
+
+    pub fn new(settings: Settings, game: Game, replay: Option<Replay>) -> Self {
+        Self {
+            version: Self::VERSION,
+            saved_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            settings,
+            game,
+            replay,
+        }
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|p| p.join("pairs-rs").join("save.json"))
+    }
+
+    /// Reads the saved game, if any. Saves written by another format version
+    /// are rejected rather than misread.
+    pub fn load() -> io::Result<Option<Self>> {
+        let Some(path) = Self::path() else {
+            return Ok(None);
+        };
+        let s = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let header: SaveHeader = serde_json::from_str(&s)?;
+        if header.version != Self::VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported save version {}", header.version),
+            ));
+        }
+        Ok(Some(serde_json::from_str(&s)?))
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "no data directory available")
+        })?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    /// Deletes the save if it's the one written at `saved_at`, leaving a save
+    /// of another game alone.
+    pub fn delete_if_saved_at(saved_at: u64) -> io::Result<()> {
+        match Self::load()? {
+            Some(save) if save.saved_at == saved_at => Self::delete(),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn delete() -> io::Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn replay_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|p| p.join("pairs-rs").join("replays"))
+}
+
+/// Writes a finished game's replay to the replay directory and returns where
+/// it went.
+pub fn save_replay(replay: &Replay) -> io::Result<PathBuf> {
+    let dir = replay_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory available"))?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "{}-{}.json",
+        replay.recorded_at,
+        replay.start().seed()
+    ));
+    fs::write(&path, serde_json::to_string(replay)?)?;
+    Ok(path)
+}
+
+pub fn load_replay(path: &Path) -> io::Result<Replay> {
+    let s = fs::read_to_string(path)?;
+    let header: SaveHeader = serde_json::from_str(&s)?;
+    if header.version != Replay::VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported replay version {}", header.version),
+        ));
+    }
+    Ok(serde_json::from_str(&s)?)
+}
@@ -0,0 +1,427 @@
+use super::{
+    keymap::{Action, Context},
+    settings::{Settings, SettingsField},
+    state::{InputMode, PopupMsg, Screen},
+    ui, App,
+};
+use crate::core::{
+    ai::Difficulty,
+    game::{Game, Phase},
+};
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+
+/// How a screen reacts to input. Navigation goes through the app's screen
+/// stack, so a screen only needs to know where it leads, not where it came
+/// from.
+pub trait ScreenHandler {
+    /// The keymap context keys are read in.
+    fn context(&self, _app: &App) -> Context {
+        Context::Menu
+    }
+
+    /// `action` is what the key is bound to, or `None` while typing. Returns
+    /// false to exit.
+    fn on_key(&self, app: &mut App, key: KeyEvent, action: Option<Action>) -> bool;
+
+    /// `clicked` is false when the mouse only moved. Returns false to exit.
+    fn on_mouse(&self, _app: &mut App, _mouse: MouseEvent, _clicked: bool) -> bool {
+        true
+    }
+}
+
+impl Screen {
+    pub fn handler(&self) -> &'static dyn ScreenHandler {
+        match self {
+            Screen::Title => &Title,
+            Screen::PlayerCountInput => &PlayerCountInput,
+            Screen::PlayerNameInput => &PlayerNameInput,
+            Screen::JoinInput => &JoinInput,
+            Screen::Gameplay => &Gameplay,
+            Screen::Replay => &Replay,
+            Screen::Leaderboard => &Leaderboard,
+            Screen::Options => &Options,
+        }
+    }
+}
+
+/// Moves the selection through a list of `len` items, wrapping around.
+fn step_index(app: &mut App, len: usize, by: i8) {
+    app.curr_index_mod = len;
+    app.curr_index = (app.curr_index + by).rem_euclid(len as i8);
+}
+
+struct Title;
+
+impl ScreenHandler for Title {
+    fn on_key(&self, app: &mut App, _key: KeyEvent, action: Option<Action>) -> bool {
+        let len = app.ui_defaults.all_buttons.len();
+        match action {
+            Some(Action::Down | Action::Right) => step_index(app, len, 1),
+            Some(Action::Up | Action::Left) => step_index(app, len, -1),
+            Some(Action::Confirm) => return app.press_title_button(),
+            _ => {}
+        }
+        true
+    }
+
+    fn on_mouse(&self, app: &mut App, mouse: MouseEvent, clicked: bool) -> bool {
+        let buttons = app.ui_defaults.all_buttons.len();
+        let area = app.ui_var.term_size;
+        let Some(i) = ui::title_button_at(area, buttons, mouse.column, mouse.row) else {
+            return true;
+        };
+        app.curr_index = i as i8;
+        !clicked || app.press_title_button()
+    }
+}
+
+struct PlayerCountInput;
+
+impl ScreenHandler for PlayerCountInput {
+    fn on_key(&self, app: &mut App, key: KeyEvent, action: Option<Action>) -> bool {
+        match app.input_mode {
+            InputMode::Normal => match action {
+                Some(Action::Edit) => app.input_mode = InputMode::Action1,
+                Some(Action::Back) => app.pop_screen(),
+                _ => {}
+            },
+            InputMode::Action1 => match key.code {
+                KeyCode::Enter => {
+                    let (min, max) = app.ui_defaults.player_count_bounds;
+                    match app.input.parse::<u8>() {
+                        Ok(n) if (min..=max).contains(&n) => {
+                            app.ui_var.player_count = n;
+                            app.ui_var.player_names.clear();
+                            app.ui_var.player_bots.clear();
+                            app.push_screen(Screen::PlayerNameInput);
+                        }
+                        _ => {
+                            app.popup = Some(PopupMsg::warn(format!(
+                                "Player count must be between {min} and {max}."
+                            )));
+                        }
+                    }
+                }
+                KeyCode::Char(c) if c.is_ascii_digit() => app.input.push(c),
+                KeyCode::Backspace => {
+                    app.input.pop();
+                }
+                KeyCode::Esc => app.input_mode = InputMode::Normal,
+                _ => {}
+            },
+        }
+        true
+    }
+}
+
+struct JoinInput;
+
+impl ScreenHandler for JoinInput {
+    fn on_key(&self, app: &mut App, key: KeyEvent, action: Option<Action>) -> bool {
+        match app.input_mode {
+            InputMode::Normal => match action {
+                Some(Action::Edit) => app.input_mode = InputMode::Action1,
+                Some(Action::Back) => app.pop_screen(),
+                _ => {}
+            },
+            InputMode::Action1 => match key.code {
+                KeyCode::Enter => {
+                    let input = app.input.trim().to_owned();
+                    match input.split_once('@') {
+                        Some((name, addr)) if !name.is_empty() && !addr.is_empty() => {
+                            app.join_game(name, addr)
+                        }
+                        _ => {
+                            app.popup = Some(PopupMsg::warn(
+                                "Enter your name and the host as name@host[:port].".into(),
+                            ));
+                        }
+                    }
+                }
+                KeyCode::Char(c) => app.input.push(c),
+                KeyCode::Backspace => {
+                    app.input.pop();
+                }
+                KeyCode::Esc => app.input_mode = InputMode::Normal,
+                _ => {}
+            },
+        }
+        true
+    }
+}
+
+struct PlayerNameInput;
+
+impl ScreenHandler for PlayerNameInput {
+    fn on_key(&self, app: &mut App, key: KeyEvent, action: Option<Action>) -> bool {
+        match app.input_mode {
+            InputMode::Normal => match action {
+                Some(Action::Edit) => app.input_mode = InputMode::Action1,
+                Some(Action::Back) => app.pop_screen(),
+                Some(Action::Confirm) => {
+                    let remaining =
+                        app.ui_var.player_count as usize - app.ui_var.player_names.len();
+                    if remaining > 0 {
+                        app.popup = Some(PopupMsg::warn(format!(
+                            "{remaining} more player name(s) required."
+                        )));
+                        return true;
+                    }
+                    app.start_game();
+                }
+                _ => {}
+            },
+            InputMode::Action1 => match key.code {
+                KeyCode::Enter => {
+                    app.input = app.input.trim().into();
+                    if app.ui_var.player_names.len() >= app.ui_var.player_count as usize {
+                        app.popup = Some(PopupMsg::warn(format!(
+                            "All {} players already have names.",
+                            app.ui_var.player_count
+                        )));
+                        return true;
+                    }
+                    if app.input.is_empty() {
+                        app.popup = Some(PopupMsg::warn("Name is required.".into()));
+                        return true;
+                    }
+                    if app.ui_var.player_names.contains(&app.input) {
+                        app.popup = Some(PopupMsg::warn(format!(
+                            "Name \"{}\" already exists.",
+                            &app.input
+                        )));
+                        return true;
+                    }
+                    app.ui_var.player_names.push(app.input.drain(..).collect());
+                    app.ui_var.player_bots.push(app.ui_var.next_bot);
+                }
+                KeyCode::Tab => {
+                    app.ui_var.next_bot = match app.ui_var.next_bot {
+                        None => Some(Difficulty::ALL[0]),
+                        Some(d) => Difficulty::ALL
+                            .iter()
+                            .skip_while(|&&x| x != d)
+                            .nth(1)
+                            .copied(),
+                    };
+                }
+                KeyCode::Left | KeyCode::Right => {
+                    if let Some(d) = app.ui_var.next_bot {
+                        let step = if key.code == KeyCode::Left { -10 } else { 10 };
+                        let memory = (d.memory() as i16 + step).clamp(0, 100) as u8;
+                        app.ui_var.next_bot = Some(Difficulty::Custom(memory));
+                    }
+                }
+                KeyCode::Char(c) => app.input.push(c),
+                KeyCode::Backspace => {
+                    app.input.pop();
+                }
+                KeyCode::Esc => app.input_mode = InputMode::Normal,
+                _ => {}
+            },
+        }
+        true
+    }
+}
+
+struct Gameplay;
+
+impl ScreenHandler for Gameplay {
+    fn context(&self, app: &App) -> Context {
+        if app.game.as_ref().is_some_and(Game::is_over) {
+            Context::GameOver
+        } else {
+            Context::Game
+        }
+    }
+
+    fn on_key(&self, app: &mut App, _key: KeyEvent, action: Option<Action>) -> bool {
+        let Some(game) = app.game.as_ref() else {
+            if let Some(Action::Back) = action {
+                app.quit_game();
+            }
+            return true;
+        };
+        if !app.is_local_turn() && !game.is_over() {
+            if let Some(Action::Back) = action {
+                app.save_game();
+                app.quit_game();
+            }
+            return true;
+        }
+        match action {
+            Some(Action::Undo) => {
+                app.step_history(false);
+                return true;
+            }
+            Some(Action::Redo) => {
+                app.step_history(true);
+                return true;
+            }
+            _ => {}
+        }
+        match game.phase() {
+            Phase::Over => {
+                match action {
+                    Some(Action::Confirm | Action::Back) => {
+                        app.save_game();
+                        app.quit_game();
+                    }
+                    Some(Action::WatchReplay) => {
+                        if let Some(replay) = app.replay.clone() {
+                            app.watch_replay(replay);
+                        }
+                    }
+                    _ => {}
+                }
+                return true;
+            }
+            Phase::Mismatch(..) => {
+                if app.can_flip_back() {
+                    app.flip_back();
+                }
+                return true;
+            }
+            _ => {}
+        }
+        match action {
+            Some(Action::Up) => app.move_cursor(-1, 0),
+            Some(Action::Down) => app.move_cursor(1, 0),
+            Some(Action::Left) => app.move_cursor(0, -1),
+            Some(Action::Right) => app.move_cursor(0, 1),
+            Some(Action::Confirm | Action::Flip) => app.reveal(app.ui_var.cursor),
+            Some(Action::Back) => {
+                app.save_game();
+                app.quit_game();
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn on_mouse(&self, app: &mut App, mouse: MouseEvent, clicked: bool) -> bool {
+        let Some(game) = &app.game else { return true };
+        if !app.is_local_turn() || game.is_over() {
+            return true;
+        }
+        if let Phase::Mismatch(..) = game.phase() {
+            if clicked && app.can_flip_back() {
+                app.flip_back();
+            }
+            return true;
+        }
+        let Some(pos) = ui::tile_at(app.ui_var.term_size, game, mouse.column, mouse.row) else {
+            return true;
+        };
+        app.ui_var.cursor = pos;
+        if clicked {
+            app.reveal(pos);
+        }
+        true
+    }
+}
+
+struct Replay;
+
+impl ScreenHandler for Replay {
+    fn context(&self, _app: &App) -> Context {
+        Context::Replay
+    }
+
+    fn on_key(&self, app: &mut App, key: KeyEvent, action: Option<Action>) -> bool {
+        let Some(viewer) = app.viewer.as_mut() else {
+            app.leave_replay();
+            return true;
+        };
+        match app.input_mode {
+            InputMode::Normal => match action {
+                Some(Action::Left) => viewer.back(),
+                Some(Action::Right) => viewer.forward(),
+                Some(Action::First) => viewer.seek(0),
+                Some(Action::Last) => viewer.seek(viewer.last_step()),
+                Some(Action::Pause) => viewer.toggle_play(),
+                Some(Action::Faster) => viewer.change_speed(1),
+                Some(Action::Slower) => viewer.change_speed(-1),
+                Some(Action::Jump) => app.input_mode = InputMode::Action1,
+                Some(Action::Back) => app.leave_replay(),
+                _ => {}
+            },
+            InputMode::Action1 => match key.code {
+                KeyCode::Enter => {
+                    match app.input.parse::<usize>() {
+                        Ok(n) if n <= viewer.last_step() => viewer.seek(n),
+                        _ => {
+                            app.popup = Some(PopupMsg::warn(format!(
+                                "Move must be between 0 and {}.",
+                                viewer.last_step()
+                            )));
+                        }
+                    }
+                    app.input.clear();
+                    app.input_mode = InputMode::Normal;
+                }
+                KeyCode::Char(c) if c.is_ascii_digit() => app.input.push(c),
+                KeyCode::Backspace => {
+                    app.input.pop();
+                }
+                KeyCode::Esc => {
+                    app.input.clear();
+                    app.input_mode = InputMode::Normal;
+                }
+                _ => {}
+            },
+        }
+        true
+    }
+}
+
+struct Leaderboard;
+
+impl ScreenHandler for Leaderboard {
+    fn on_key(&self, app: &mut App, _key: KeyEvent, action: Option<Action>) -> bool {
+        if let Some(Action::Back | Action::Confirm) = action {
+            app.stats = None;
+            app.pop_screen();
+        }
+        true
+    }
+}
+
+struct Options;
+
+impl ScreenHandler for Options {
+    fn on_key(&self, app: &mut App, _key: KeyEvent, action: Option<Action>) -> bool {
+        let len = SettingsField::ALL.len();
+        let field = SettingsField::ALL[app.curr_index as usize];
+        match action {
+            Some(Action::Down) => step_index(app, len, 1),
+            Some(Action::Up) => step_index(app, len, -1),
+            Some(Action::Right | Action::Confirm) => {
+                app.settings.adjust(field, 1);
+                app.load_palette();
+                if field == SettingsField::Tiles {
+                    app.check_tiles();
+                }
+            }
+            Some(Action::Left) => {
+                app.settings.adjust(field, -1);
+                app.load_palette();
+                if field == SettingsField::Tiles {
+                    app.check_tiles();
+                }
+            }
+            Some(Action::Reset) => {
+                app.settings = Settings::default();
+                app.load_palette();
+            }
+            Some(Action::Back) => {
+                if let Err(err) = app.settings.save() {
+                    app.popup = Some(PopupMsg::err(format!("Failed to save settings: {err}")));
+                }
+                app.pop_screen();
+            }
+            _ => {}
+        }
+        true
+    }
+}
@@ -0,0 +1,365 @@
+use super::{theme, tiles};
+use crate::core::{
+    cards::{Deck, DeckKind, DeckSpec},
+    rules::PairRuleKind,
+    tiles::AnyTile,
+    utils,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::PathBuf, time::Duration};
+use tui::style::Color;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Accent {
+    #[default]
+    Ocean,
+    Forest,
+    Orchid,
+    Sunset,
+}
+
+impl Accent {
+    pub const ALL: [Accent; 4] = [
+        Accent::Ocean,
+        Accent::Forest,
+        Accent::Orchid,
+        Accent::Sunset,
+    ];
+
+    pub fn color(&self) -> Color {
+        match self {
+            Accent::Ocean => Color::LightBlue,
+            Accent::Forest => Color::LightGreen,
+            Accent::Orchid => Color::LightMagenta,
+            Accent::Sunset => Color::LightYellow,
+        }
+    }
+}
+
+impl fmt::Display for Accent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardBack {
+    #[default]
+    Shade,
+    Cross,
+    Dots,
+    Plain,
+}
+
+impl CardBack {
+    pub const ALL: [CardBack; 4] = [
+        CardBack::Shade,
+        CardBack::Cross,
+        CardBack::Dots,
+        CardBack::Plain,
+    ];
+
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            CardBack::Shade => "░░░",
+            CardBack::Cross => "╳╳╳",
+            CardBack::Dots => "•••",
+            CardBack::Plain => "",
+        }
+    }
+}
+
+impl fmt::Display for CardBack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeedMode {
+    #[default]
+    Random,
+    Daily,
+}
+
+impl SeedMode {
+    pub const ALL: [SeedMode; 2] = [SeedMode::Random, SeedMode::Daily];
+
+    pub fn seed(&self) -> u64 {
+        match self {
+            SeedMode::Random => utils::random_seed(),
+            SeedMode::Daily => utils::daily_seed(),
+        }
+    }
+}
+
+impl fmt::Display for SeedMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimerMode {
+    #[default]
+    Off,
+    Countdown,
+    TurnLimit,
+    Speedrun,
+}
+
+impl TimerMode {
+    pub const ALL: [TimerMode; 4] = [
+        TimerMode::Off,
+        TimerMode::Countdown,
+        TimerMode::TurnLimit,
+        TimerMode::Speedrun,
+    ];
+}
+
+impl fmt::Display for TimerMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            TimerMode::Off => "Off",
+            TimerMode::Countdown => "Countdown",
+            TimerMode::TurnLimit => "Turn limit",
+            TimerMode::Speedrun => "Speedrun",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingsField {
+    Tiles,
+    DeckKind,
+    DeckCount,
+    Jokers,
+    BoardSize,
+    PairRule,
+    SeedMode,
+    FlipBackDelay,
+    Timer,
+    TimeLimit,
+    TurnLimit,
+    Theme,
+    Accent,
+    CardBack,
+}
+
+impl SettingsField {
+    pub const ALL: [SettingsField; 14] = [
+        SettingsField::Tiles,
+        SettingsField::DeckKind,
+        SettingsField::DeckCount,
+        SettingsField::Jokers,
+        SettingsField::BoardSize,
+        SettingsField::PairRule,
+        SettingsField::SeedMode,
+        SettingsField::FlipBackDelay,
+        SettingsField::Timer,
+        SettingsField::TimeLimit,
+        SettingsField::TurnLimit,
+        SettingsField::Theme,
+        SettingsField::Accent,
+        SettingsField::CardBack,
+    ];
+}
+
+impl fmt::Display for SettingsField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            SettingsField::Tiles => "Tiles",
+            SettingsField::DeckKind => "Deck",
+            SettingsField::DeckCount => "Stacked decks",
+            SettingsField::Jokers => "Jokers per deck",
+            SettingsField::BoardSize => "Board size",
+            SettingsField::PairRule => "Pairing rule",
+            SettingsField::SeedMode => "Seed",
+            SettingsField::FlipBackDelay => "Flip-back delay",
+            SettingsField::Timer => "Timer",
+            SettingsField::TimeLimit => "Countdown length",
+            SettingsField::TurnLimit => "Time per turn",
+            SettingsField::Theme => "Theme",
+            SettingsField::Accent => "Accent color",
+            SettingsField::CardBack => "Card back",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub tiles: String,
+    pub deck: DeckSpec,
+    pub board_size: u8,
+    pub pair_rule: PairRuleKind,
+    pub seed_mode: SeedMode,
+    pub flip_back_delay_ms: u64,
+    pub timer: TimerMode,
+    pub time_limit_secs: u16,
+    pub turn_limit_secs: u8,
+    pub theme: String,
+    pub accent: Accent,
+    pub card_back: CardBack,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            tiles: tiles::BUILT_IN[0].to_owned(),
+            deck: DeckSpec::default(),
+            board_size: 8,
+            pair_rule: PairRuleKind::default(),
+            seed_mode: SeedMode::default(),
+            flip_back_delay_ms: 800,
+            timer: TimerMode::default(),
+            time_limit_secs: 180,
+            turn_limit_secs: 10,
+            theme: theme::BUILT_IN[0].to_owned(),
+            accent: Accent::default(),
+            card_back: CardBack::default(),
+        }
+    }
+}
+
+fn cycle<T: Copy + PartialEq>(all: &[T], curr: T, step: i8) -> T {
+    let i = all.iter().position(|&x| x == curr).unwrap_or(0) as i8;
+    all[(i + step).rem_euclid(all.len() as i8) as usize]
+}
+
+impl Settings {
+    pub const DECK_COUNT_BOUNDS: (u8, u8) = (1, 4);
+    pub const JOKERS_MAX: u8 = 2;
+    pub const BOARD_SIZE_BOUNDS: (u8, u8) = (1, 112);
+    pub const FLIP_BACK_DELAY_MAX_MS: u64 = 3000;
+    const FLIP_BACK_DELAY_STEP_MS: u64 = 100;
+    pub const TIME_LIMIT_BOUNDS: (u16, u16) = (30, 1800);
+    const TIME_LIMIT_STEP: u16 = 30;
+    pub const TURN_LIMIT_BOUNDS: (u8, u8) = (3, 60);
+
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("pairs-rs").join("settings.toml"))
+    }
+
+    /// Reads the settings file, falling back to defaults when there is none yet.
+    pub fn load() -> io::Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match fs::read_to_string(path) {
+            Ok(s) => toml::from_str(&s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "no config directory available")
+        })?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let s = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, s)
+    }
+
+    pub fn flip_back_delay(&self) -> Duration {
+        Duration::from_millis(self.flip_back_delay_ms)
+    }
+
+    pub fn time_limit(&self) -> Duration {
+        Duration::from_secs(self.time_limit_secs as u64)
+    }
+
+    pub fn turn_limit(&self) -> Duration {
+        Duration::from_secs(self.turn_limit_secs as u64)
+    }
+
+    /// The deck games are dealt from: the playing cards paired up under the
+    /// pairing rule, or the pairs of the tile set.
+    pub fn paired_deck(&self) -> io::Result<Deck<AnyTile>> {
+        Ok(match tiles::load(&self.tiles)? {
+            None => Deck::from_spec(&self.deck)
+                .into_paired_by(&self.pair_rule)
+                .map(AnyTile::Card),
+            Some(deck) => deck,
+        })
+    }
+
+    pub fn value_of(&self, field: SettingsField) -> String {
+        match field {
+            SettingsField::Tiles => self.tiles.clone(),
+            SettingsField::DeckKind => self.deck.kind.to_string(),
+            SettingsField::DeckCount => self.deck.decks.to_string(),
+            SettingsField::Jokers => self.deck.jokers.to_string(),
+            SettingsField::BoardSize => format!("{} pairs", self.board_size),
+            SettingsField::PairRule => self.pair_rule.to_string(),
+            SettingsField::SeedMode => self.seed_mode.to_string(),
+            SettingsField::FlipBackDelay => format!("{} ms", self.flip_back_delay_ms),
+            SettingsField::Timer => self.timer.to_string(),
+            SettingsField::TimeLimit => format!(
+                "{}:{:02}",
+                self.time_limit_secs / 60,
+                self.time_limit_secs % 60
+            ),
+            SettingsField::TurnLimit => format!("{} s", self.turn_limit_secs),
+            SettingsField::Theme => self.theme.clone(),
+            SettingsField::Accent => self.accent.to_string(),
+            SettingsField::CardBack => self.card_back.to_string(),
+        }
+    }
+
+    pub fn adjust(&mut self, field: SettingsField, step: i8) {
+        let step_within = |v: u8, (min, max): (u8, u8)| {
+            (v as i16 + step as i16).clamp(min as i16, max as i16) as u8
+        };
+        match field {
+            SettingsField::Tiles => {
+                let names = tiles::names();
+                let i = names.iter().position(|n| *n == self.tiles).unwrap_or(0) as i8;
+                self.tiles = names[(i + step).rem_euclid(names.len() as i8) as usize].clone();
+            }
+            SettingsField::DeckKind => self.deck.kind = cycle(&DeckKind::ALL, self.deck.kind, step),
+            SettingsField::DeckCount => {
+                self.deck.decks = step_within(self.deck.decks, Self::DECK_COUNT_BOUNDS)
+            }
+            SettingsField::Jokers => {
+                self.deck.jokers = step_within(self.deck.jokers, (0, Self::JOKERS_MAX))
+            }
+            SettingsField::BoardSize => {
+                self.board_size = step_within(self.board_size, Self::BOARD_SIZE_BOUNDS)
+            }
+            SettingsField::PairRule => {
+                self.pair_rule = cycle(&PairRuleKind::ALL, self.pair_rule, step)
+            }
+            SettingsField::SeedMode => self.seed_mode = cycle(&SeedMode::ALL, self.seed_mode, step),
+            SettingsField::FlipBackDelay => {
+                let delta = Self::FLIP_BACK_DELAY_STEP_MS * step.unsigned_abs() as u64;
+                self.flip_back_delay_ms = if step < 0 {
+                    self.flip_back_delay_ms.saturating_sub(delta)
+                } else {
+                    (self.flip_back_delay_ms + delta).min(Self::FLIP_BACK_DELAY_MAX_MS)
+                };
+            }
+            SettingsField::Timer => self.timer = cycle(&TimerMode::ALL, self.timer, step),
+            SettingsField::TimeLimit => {
+                let (min, max) = Self::TIME_LIMIT_BOUNDS;
+                let v = self.time_limit_secs as i32 + (Self::TIME_LIMIT_STEP as i32 * step as i32);
+                self.time_limit_secs = v.clamp(min as i32, max as i32) as u16;
+            }
+            SettingsField::TurnLimit => {
+                self.turn_limit_secs = step_within(self.turn_limit_secs, Self::TURN_LIMIT_BOUNDS)
+            }
+            SettingsField::Theme => {
+                let names = theme::names();
+                let i = names.iter().position(|n| *n == self.theme).unwrap_or(0) as i8;
+                self.theme = names[(i + step).rem_euclid(names.len() as i8) as usize].clone();
+            }
+            SettingsField::Accent => self.accent = cycle(&Accent::ALL, self.accent, step),
+            SettingsField::CardBack => self.card_back = cycle(&CardBack::ALL, self.card_back, step),
+        }
+    }
+}
@@ -3,6 +3,10 @@ use std::fmt;
 #[derive(Debug)]
 pub enum TitleButtons {
     Start,
+    Host,
+    Join,
+    Continue,
+    Leaderboard,
     Options,
     Exit,
 }
@@ -48,10 +52,14 @@ impl PopupMsg {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Screen {
     Title,
     PlayerCountInput,
     PlayerNameInput,
+    JoinInput,
     Gameplay,
+    Replay,
+    Leaderboard,
     Options,
 }
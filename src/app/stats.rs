@@ -0,0 +1,252 @@
+use crate::core::{ai::Difficulty, game::Game};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerRecord {
+    pub name: String,
+    #[serde(default)]
+    pub bot: Option<Difficulty>,
+    pub pairs: usize,
+    pub moves: usize,
+    pub misses: u32,
+    pub won: bool,
+}
+
+/// One finished game, as kept in the stats store.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub finished_at: u64,
+    pub seed: u64,
+    pub board_size: usize,
+    pub time_ms: u64,
+    pub players: Vec<PlayerRecord>,
+}
+
+impl GameRecord {
+    /// Only a game that cleared the board has winners; one cut short by the
+    /// clock counts as played, but not won.
+    pub fn new(game: &Game, time: Duration) -> Self {
+        let winners = if game.board().is_cleared() {
+            game.winners().iter().map(|p| p.name.clone()).collect_vec()
+        } else {
+            vec![]
+        };
+        Self {
+            finished_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            seed: game.seed(),
+            board_size: game.size() as usize,
+            time_ms: time.as_millis() as u64,
+            players: game
+                .players()
+                .iter()
+                .map(|p| PlayerRecord {
+                    name: p.name.clone(),
+                    bot: p.bot,
+                    pairs: p.pairs(),
+                    moves: p.moves(),
+                    misses: p.misses,
+                    won: winners.contains(&p.name),
+                })
+                .collect(),
+        }
+    }
+}
+
+pub struct PlayerStats {
+    pub name: String,
+    pub is_bot: bool,
+    pub played: usize,
+    pub won: usize,
+    pub pairs: usize,
+    pub moves: usize,
+}
+
+impl PlayerStats {
+    pub fn win_rate(&self) -> f64 {
+        self.won as f64 / self.played.max(1) as f64
+    }
+
+    /// Share of moves that found a pair.
+    pub fn accuracy(&self) -> f64 {
+        self.pairs as f64 / self.moves.max(1) as f64
+    }
+}
+
+pub struct BestTime {
+    pub board_size: usize,
+    pub time_ms: u64,
+    pub name: String,
+    pub seed: u64,
+}
+
+#[derive(Deserialize)]
+struct StatsHeader {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Stats {
+    pub version: u32,
+    pub games: Vec<GameRecord>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            games: vec![],
+        }
+    }
+}
+
+impl Stats {
+    pub const VERSION: u32 = 1;
+
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|p| p.join("pairs-rs").join("stats.json"))
+    }
+
+    pub fn load() -> io::Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let s = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        let header: StatsHeader = serde_json::from_str(&s)?;
+        if header.version != Self::VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported stats version {}", header.version),
+            ));
+        }
+        Ok(serde_json::from_str(&s)?)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "no data directory available")
+        })?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    /// Adds a finished game to the store on disk.
+    pub fn record(record: GameRecord) -> io::Result<()> {
+        let mut stats = Self::load()?;
+        stats.games.push(record);
+        stats.save()
+    }
+
+    /// Totals for everyone who has played, best win rate first.
+    pub fn players(&self) -> Vec<PlayerStats> {
+        self.games
+            .iter()
+            .flat_map(|g| &g.players)
+            .into_group_map_by(|p| p.name.clone())
+            .into_iter()
+            .map(|(name, records)| PlayerStats {
+                name,
+                is_bot: records.iter().any(|r| r.bot.is_some()),
+                played: records.len(),
+                won: records.iter().filter(|r| r.won).count(),
+                pairs: records.iter().map(|r| r.pairs).sum(),
+                moves: records.iter().map(|r| r.moves).sum(),
+            })
+            .sorted_by(|a, b| {
+                b.win_rate()
+                    .total_cmp(&a.win_rate())
+                    .then(b.played.cmp(&a.played))
+                    .then_with(|| a.name.cmp(&b.name))
+            })
+            .collect()
+    }
+
+    /// The fastest win for each board size, smallest board first.
+    pub fn best_times(&self) -> Vec<BestTime> {
+        self.games
+            .iter()
+            .flat_map(|g| {
+                g.players.iter().filter(|p| p.won).map(|p| BestTime {
+                    board_size: g.board_size,
+                    time_ms: g.time_ms,
+                    name: p.name.clone(),
+                    seed: g.seed,
+                })
+            })
+            .into_group_map_by(|t| t.board_size)
+            .into_values()
+            .filter_map(|times| times.into_iter().min_by_key(|t| t.time_ms))
+            .sorted_by_key(|t| t.board_size)
+            .collect()
+    }
+}
+
+/// Formats a duration as `m:ss.t`.
+pub fn format_time(ms: u64) -> String {
+    let secs = ms / 1000;
+    format!("{}:{:02}.{}", secs / 60, secs % 60, ms % 1000 / 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::game::fixtures::{card_game, partner};
+
+    #[test]
+    fn time_up_records_the_dealt_size_and_no_winner() {
+        let mut game = card_game(4);
+        let (first, _) = game.board().slots().next().unwrap();
+        let second = partner(&game, first);
+        game.reveal(first).unwrap();
+        game.reveal(second).unwrap();
+        game.time_up();
+
+        let record = GameRecord::new(&game, Duration::from_secs(30));
+        assert_eq!(record.board_size, 4);
+        assert!(record.players.iter().all(|p| !p.won));
+
+        let stats = Stats {
+            games: vec![record],
+            ..Stats::default()
+        };
+        assert!(stats.best_times().is_empty());
+    }
+
+    #[test]
+    fn cleared_board_records_the_winner() {
+        let mut game = card_game(4);
+        while !game.is_over() {
+            let (first, _) = game.board().slots().next().unwrap();
+            let second = partner(&game, first);
+            game.reveal(first).unwrap();
+            game.reveal(second).unwrap();
+        }
+
+        let record = GameRecord::new(&game, Duration::from_secs(12));
+        assert_eq!(record.board_size, 4);
+        let won = record.players.iter().filter(|p| p.won).collect_vec();
+        assert_eq!(won.len(), 1);
+        assert_eq!(won[0].name, "Ann");
+
+        let stats = Stats {
+            games: vec![record],
+            ..Stats::default()
+        };
+        let best = stats.best_times();
+        assert_eq!(best.len(), 1);
+        assert_eq!(best[0].time_ms, 12_000);
+    }
+}
@@ -0,0 +1,197 @@
+use super::settings::Accent;
+use crate::core::cards::SuitColor;
+use serde::Deserialize;
+use std::{fs, io, path::PathBuf};
+use tui::style::{Color, Style};
+
+pub const BUILT_IN: [&str; 4] = ["Dark", "Light", "High contrast", "Monochrome"];
+
+/// The colors everything on screen is drawn with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub accent: Color,
+    pub border: Color,
+    pub red_suit: Color,
+    pub black_suit: Color,
+    pub card_back: Color,
+    pub info: Color,
+    pub warn: Color,
+    pub err: Color,
+}
+
+/// A user theme file. Every key is optional and falls back to `base`, one of
+/// the built-in themes, or to the dark theme.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    base: Option<String>,
+    accent: Option<String>,
+    border: Option<String>,
+    red_suit: Option<String>,
+    black_suit: Option<String>,
+    card_back: Option<String>,
+    info: Option<String>,
+    warn: Option<String>,
+    err: Option<String>,
+}
+
+impl Palette {
+    /// One of the [`BUILT_IN`] themes, taking its accent from `accent` where
+    /// the theme has no accent of its own.
+    pub fn built_in(name: &str, accent: Accent) -> Option<Self> {
+        let accent = accent.color();
+        Some(match name {
+            "Dark" => Self {
+                accent,
+                border: Color::DarkGray,
+                red_suit: Color::LightRed,
+                black_suit: Color::White,
+                card_back: accent,
+                info: accent,
+                warn: Color::Yellow,
+                err: Color::Red,
+            },
+            "Light" => Self {
+                accent,
+                border: Color::Gray,
+                red_suit: Color::Red,
+                black_suit: Color::Black,
+                card_back: accent,
+                info: accent,
+                warn: Color::Rgb(176, 112, 0),
+                err: Color::Red,
+            },
+            "High contrast" => Self {
+                accent: Color::LightCyan,
+                border: Color::White,
+                red_suit: Color::LightRed,
+                black_suit: Color::White,
+                card_back: Color::LightCyan,
+                info: Color::LightCyan,
+                warn: Color::LightYellow,
+                err: Color::LightRed,
+            },
+            "Monochrome" => Self {
+                accent: Color::Reset,
+                border: Color::Reset,
+                red_suit: Color::Reset,
+                black_suit: Color::Reset,
+                card_back: Color::Reset,
+                info: Color::Reset,
+                warn: Color::Reset,
+                err: Color::Reset,
+            },
+            _ => return None,
+        })
+    }
+
+    /// A built-in theme, or the theme file of that name.
+    pub fn load(name: &str, accent: Accent) -> io::Result<Self> {
+        if let Some(palette) = Self::built_in(name, accent) {
+            return Ok(palette);
+        }
+        let path = themes_dir()
+            .map(|dir| dir.join(format!("{name}.toml")))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        let s = fs::read_to_string(&path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("theme \"{name}\" ({}): {e}", path.display()),
+            )
+        })?;
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let file: ThemeFile =
+            toml::from_str(&s).map_err(|e| invalid(format!("theme \"{name}\": {e}")))?;
+
+        let base = file.base.as_deref().unwrap_or(BUILT_IN[0]);
+        let mut palette = Self::built_in(base, accent)
+            .ok_or_else(|| invalid(format!("theme \"{name}\": unknown base theme \"{base}\"")))?;
+        for (key, value, slot) in [
+            ("accent", &file.accent, &mut palette.accent),
+            ("border", &file.border, &mut palette.border),
+            ("red_suit", &file.red_suit, &mut palette.red_suit),
+            ("black_suit", &file.black_suit, &mut palette.black_suit),
+            ("card_back", &file.card_back, &mut palette.card_back),
+            ("info", &file.info, &mut palette.info),
+            ("warn", &file.warn, &mut palette.warn),
+            ("err", &file.err, &mut palette.err),
+        ] {
+            if let Some(value) = value {
+                *slot = parse_color(value).ok_or_else(|| {
+                    invalid(format!(
+                        "theme \"{name}\": unknown color \"{value}\" for {key}"
+                    ))
+                })?;
+            }
+        }
+        Ok(palette)
+    }
+
+    pub fn suit(&self, color: SuitColor) -> Color {
+        match color {
+            SuitColor::Red => self.red_suit,
+            SuitColor::Black => self.black_suit,
+        }
+    }
+
+    pub fn border(&self) -> Style {
+        Style::default().fg(self.border)
+    }
+}
+
+pub fn themes_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("pairs-rs").join("themes"))
+}
+
+/// The built-in themes followed by the user's theme files, by name.
+pub fn names() -> Vec<String> {
+    let mut files = themes_dir()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let is_toml = path.extension().is_some_and(|ext| ext == "toml");
+            is_toml.then(|| path.file_stem()?.to_str().map(str::to_owned))?
+        })
+        .filter(|name| !BUILT_IN.contains(&name.as_str()))
+        .collect::<Vec<_>>();
+    files.sort();
+    BUILT_IN
+        .iter()
+        .map(|&s| s.to_owned())
+        .chain(files)
+        .collect()
+}
+
+/// Reads a color written as a name like `light-blue`, or as `#rrggbb`.
+pub fn parse_color(s: &str) -> Option<Color> {
+    if let Some(hex) = s.strip_prefix('#') {
+        let n = u32::from_str_radix(hex, 16)
+            .ok()
+            .filter(|_| hex.len() == 6)?;
+        return Some(Color::Rgb((n >> 16) as u8, (n >> 8) as u8, n as u8));
+    }
+    Some(
+        match s.to_ascii_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "default" | "reset" => Color::Reset,
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "gray" | "grey" => Color::Gray,
+            "darkgray" | "darkgrey" => Color::DarkGray,
+            "lightred" => Color::LightRed,
+            "lightgreen" => Color::LightGreen,
+            "lightyellow" => Color::LightYellow,
+            "lightblue" => Color::LightBlue,
+            "lightmagenta" => Color::LightMagenta,
+            "lightcyan" => Color::LightCyan,
+            "white" => Color::White,
+            _ => return None,
+        },
+    )
+}
@@ -0,0 +1,365 @@
+use super::theme;
+use crate::core::{
+    cards::Deck,
+    tiles::{self, AnyTile, PairedTile},
+};
+use serde::{
+    de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use std::{collections::HashSet, fmt, fs, io, path::PathBuf};
+
+pub const BUILT_IN: [&str; 5] = ["Playing cards", "Emoji", "Letters", "Numbers", "Words"];
+
+/// File types a tile set can be read from: a list of symbols, or a deck file
+/// of pairs.
+const EXTENSIONS: [&str; 3] = ["txt", "toml", "json"];
+
+pub fn tiles_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("pairs-rs").join("tiles"))
+}
+
+/// The built-in tile sets followed by the user's lists and deck files, by
+/// name.
+pub fn names() -> Vec<String> {
+    let mut files = tiles_dir()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let known = path
+                .extension()
+                .is_some_and(|ext| EXTENSIONS.iter().any(|&e| ext == e));
+            known.then(|| path.file_stem()?.to_str().map(str::to_owned))?
+        })
+        .filter(|name| !BUILT_IN.contains(&name.as_str()))
+        .collect::<Vec<_>>();
+    files.sort();
+    files.dedup();
+    BUILT_IN
+        .iter()
+        .map(|&s| s.to_owned())
+        .chain(files)
+        .collect()
+}
+
+/// The paired deck for a tile set, or `None` for playing cards.
+///
+/// A symbol list is a text file with one symbol per line; blank lines and
+/// lines starting with `#` are skipped. A deck file is TOML or JSON with a
+/// list of `pairs`, each holding two `tiles` and an optional `color`.
+pub fn load(name: &str) -> io::Result<Option<Deck<AnyTile>>> {
+    let owned = |list: &[&str]| list.iter().map(|&s| s.to_owned()).collect::<Vec<_>>();
+    let symbols = match name {
+        "Playing cards" => return Ok(None),
+        "Emoji" => owned(&tiles::EMOJI),
+        "Letters" => tiles::letters(),
+        "Numbers" => tiles::numbers(),
+        "Words" => owned(&tiles::WORDS),
+        _ => return load_file(name).map(Some),
+    };
+    Ok(Some(tiles::symbol_deck(&symbols)))
+}
+
+fn load_file(name: &str) -> io::Result<Deck<AnyTile>> {
+    let dir = tiles_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    let path = EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{name}.{ext}")))
+        .find(|path| path.exists())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("tile set \"{name}\" not found in {}", dir.display()),
+            )
+        })?;
+    let s = fs::read_to_string(&path)
+        .map_err(|e| io::Error::new(e.kind(), format!("tile set \"{name}\": {e}")))?;
+    parse(name, path.extension().and_then(|ext| ext.to_str()), &s)
+}
+
+/// Reads a tile set from the contents of a file with extension `ext`.
+fn parse(name: &str, ext: Option<&str>, s: &str) -> io::Result<Deck<AnyTile>> {
+    let invalid = |msg: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("tile set \"{name}\": {msg}"),
+        )
+    };
+
+    let file: DeckFile = match ext {
+        Some("toml") => toml::from_str(s).map_err(|e| invalid(toml_error(s, &e)))?,
+        Some("json") => serde_json::from_str(s).map_err(|e| invalid(e.to_string()))?,
+        _ => {
+            let symbols = s
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .map(str::to_owned)
+                .collect::<Vec<_>>();
+            if symbols.is_empty() {
+                return Err(invalid("no tiles".to_owned()));
+            }
+            return Ok(tiles::symbol_deck(&symbols));
+        }
+    };
+    if file.pairs.is_empty() {
+        return Err(invalid("no pairs".to_owned()));
+    }
+    Ok(Deck::from_pairs(file.pairs.into_iter().enumerate().map(
+        |(i, pair)| {
+            pair.tiles.map(|text| {
+                AnyTile::Paired(PairedTile {
+                    text,
+                    key: i.to_string(),
+                    color: pair.color.clone(),
+                })
+            })
+        },
+    )))
+}
+
+/// A TOML error on one line, worded like serde_json's.
+fn toml_error(s: &str, e: &toml::de::Error) -> String {
+    let Some(span) = e.span() else {
+        return e.message().to_owned();
+    };
+    let before = &s[..span.start];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    format!("{} at line {line} column {column}", e.message())
+}
+
+/// A deck file. Everything is checked while it's read, so errors point at
+/// the line they're on.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeckFile {
+    #[serde(deserialize_with = "distinct_pairs")]
+    pairs: Vec<Pair>,
+}
+
+struct Pair {
+    tiles: [String; 2],
+    color: Option<String>,
+}
+
+/// Reads the pairs, refusing a tile that's already in another pair: two
+/// tiles that look the same but don't match can't be told apart.
+fn distinct_pairs<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Pair>, D::Error> {
+    struct PairsVisitor;
+
+    impl<'de> Visitor<'de> for PairsVisitor {
+        type Value = Vec<Pair>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a list of pairs")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut pairs = vec![];
+            let mut seen = HashSet::new();
+            while let Some(pair) = seq.next_element_seed(PairSeed(&mut seen))? {
+                pairs.push(pair);
+            }
+            Ok(pairs)
+        }
+    }
+
+    deserializer.deserialize_seq(PairsVisitor)
+}
+
+/// Reads one pair, given the tiles of the pairs before it.
+struct PairSeed<'a>(&'a mut HashSet<String>);
+
+impl<'de> DeserializeSeed<'de> for PairSeed<'_> {
+    type Value = Pair;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Pair, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for PairSeed<'_> {
+    type Value = Pair;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a pair with `tiles` and an optional `color`")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Pair, A::Error> {
+        let mut tiles = None;
+        let mut color = None;
+        while let Some(field) = map.next_key::<PairField>()? {
+            match field {
+                PairField::Tiles if tiles.is_none() => {
+                    tiles = Some(map.next_value_seed(TilesSeed(&mut *self.0))?);
+                }
+                PairField::Color if color.is_none() => {
+                    color = Some(map.next_value::<ColorName>()?.0);
+                }
+                PairField::Tiles => return Err(de::Error::duplicate_field("tiles")),
+                PairField::Color => return Err(de::Error::duplicate_field("color")),
+            }
+        }
+        let tiles = tiles.ok_or_else(|| de::Error::missing_field("tiles"))?;
+        Ok(Pair { tiles, color })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum PairField {
+    Tiles,
+    Color,
+}
+
+/// A color name that [`theme::parse_color`] knows.
+struct ColorName(String);
+
+impl<'de> Deserialize<'de> for ColorName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        match theme::parse_color(&name) {
+            Some(_) => Ok(ColorName(name)),
+            None => Err(de::Error::custom(format!("unknown color \"{name}\""))),
+        }
+    }
+}
+
+/// Reads the two tiles of a pair and adds them to the ones seen so far.
+struct TilesSeed<'a>(&'a mut HashSet<String>);
+
+impl<'de> DeserializeSeed<'de> for TilesSeed<'_> {
+    type Value = [String; 2];
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let tiles = Vec::<String>::deserialize(deserializer)?;
+        let tiles: [String; 2] = tiles.try_into().map_err(|t: Vec<_>| {
+            de::Error::custom(format!("a pair needs 2 tiles, found {}", t.len()))
+        })?;
+        let tiles = tiles.map(|t| t.trim().to_owned());
+        if tiles.iter().any(String::is_empty) {
+            return Err(de::Error::custom("tiles can't be empty"));
+        }
+        if let Some(tile) = tiles.iter().find(|t| self.0.contains(*t)) {
+            return Err(de::Error::custom(format!(
+                "\"{tile}\" is already in another pair"
+            )));
+        }
+        self.0.extend(tiles.iter().cloned());
+        Ok(tiles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{rules::SameKey, tiles::Tile};
+    use itertools::Itertools;
+
+    fn error(ext: &str, s: &str) -> String {
+        match parse("vocab", Some(ext), s) {
+            Ok(_) => panic!("expected an error"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn deck_file_pairs_differing_tiles() {
+        let s = "[[pairs]]\ntiles = [\"dog\", \"chien\"]\ncolor = \"red\"\n\n\
+                 [[pairs]]\ntiles = [\" cat \", \"chat\"]\n";
+        let deck = parse("vocab", Some("toml"), s).unwrap();
+        assert_eq!(deck.pair_count(&SameKey), 2);
+        let text = deck.iter().map(|t| t.to_string()).collect_vec();
+        assert_eq!(text, ["dog", "chien", "cat", "chat"]);
+        assert_ne!(deck[0].pair_key(), deck[2].pair_key());
+        let AnyTile::Paired(dog) = &deck[0] else {
+            panic!("expected a paired tile");
+        };
+        assert_eq!(dog.color.as_deref(), Some("red"));
+
+        let json = r#"{"pairs": [{"tiles": ["dog", "chien"]}, {"tiles": ["cat", "chat"]}]}"#;
+        let deck = parse("vocab", Some("json"), json).unwrap();
+        assert_eq!(deck.pair_count(&SameKey), 2);
+    }
+
+    #[test]
+    fn toml_errors_point_at_the_line() {
+        assert_eq!(
+            error(
+                "toml",
+                "[[pairs]]\ntiles = [\"dog\", \"chien\"]\n\n[[pairs]]\ntiles = [\"cat\", \"chat\", \"x\"]\n"
+            ),
+            "tile set \"vocab\": a pair needs 2 tiles, found 3 at line 5 column 9"
+        );
+        assert_eq!(
+            error(
+                "toml",
+                "[[pairs]]\ntiles = [\"dog\", \"chien\"]\ncolor = \"blurple\"\n"
+            ),
+            "tile set \"vocab\": unknown color \"blurple\" at line 3 column 9"
+        );
+        assert_eq!(
+            error(
+                "toml",
+                "[[pairs]]\ntiles = [\"dog\", \"chien\"]\n\n[[pairs]]\ntiles = [\"cat\", \"dog\"]\n"
+            ),
+            "tile set \"vocab\": \"dog\" is already in another pair at line 5 column 9"
+        );
+        assert_eq!(
+            error("toml", "pairs = [\n  { tiles = [\"dog\", \" \"] },\n]\n"),
+            "tile set \"vocab\": tiles can't be empty at line 2 column 13"
+        );
+        assert_eq!(
+            error("toml", "[[pairs]]\ntiles = [\"dog\", \"chien\"]\nsize = 3\n"),
+            "tile set \"vocab\": unknown field `size`, expected `tiles` or `color` at line 3 column 1"
+        );
+    }
+
+    #[test]
+    fn json_errors_point_at_the_line() {
+        assert_eq!(
+            error(
+                "json",
+                "{\n  \"pairs\": [\n    {\"tiles\": [\"dog\", \"chien\"]},\n    {\"tiles\": [\"cat\"]}\n  ]\n}\n"
+            ),
+            "tile set \"vocab\": a pair needs 2 tiles, found 1 at line 4 column 22"
+        );
+        assert_eq!(
+            error(
+                "json",
+                "{\n  \"pairs\": [\n    {\"tiles\": [\"dog\", \"chien\"], \"color\": \"nope\"}\n  ]\n}\n"
+            ),
+            "tile set \"vocab\": unknown color \"nope\" at line 3 column 48"
+        );
+        assert_eq!(
+            error(
+                "json",
+                "{\n  \"pairs\": [\n    {\"tiles\": [\"dog\", \"chien\"]},\n    {\"tiles\": [\"chien\", \"hund\"]}\n  ]\n}\n"
+            ),
+            "tile set \"vocab\": \"chien\" is already in another pair at line 4 column 32"
+        );
+    }
+
+    #[test]
+    fn empty_files_are_refused() {
+        assert_eq!(
+            error("toml", "pairs = []\n"),
+            "tile set \"vocab\": no pairs"
+        );
+        assert_eq!(
+            error("txt", "# nothing yet\n\n"),
+            "tile set \"vocab\": no tiles"
+        );
+    }
+
+    #[test]
+    fn symbol_lists_skip_blanks_and_comments() {
+        let deck = parse("vocab", Some("txt"), "# fruit\napple\n\n pear \napple\n").unwrap();
+        let text = deck.iter().map(|t| t.to_string()).collect_vec();
+        assert_eq!(text, ["apple", "apple", "pear", "pear"]);
+    }
+}
@@ -1,66 +1,120 @@
+use super::card::{self, CardTile};
+use super::keymap::{Action, Context};
+use super::settings::{SettingsField, TimerMode};
+use super::stats;
 use super::{
     state::{InputMode, PopupMsg, PopupSeverity, Screen, TitleButtons},
-    App,
+    App, Link,
+};
+use crate::{
+    core::{
+        game::{Game, Phase, Pos},
+        replay::EventKind,
+        utils, Board,
+    },
+    net,
 };
 use itertools::Itertools;
+use std::time::Duration;
 use tui::{
     backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans, Text},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Cell, Clear, List, ListItem, Paragraph, Row, Table, Wrap},
     Frame,
 };
 use unicode_width::UnicodeWidthStr;
 
-fn title<B: Backend>(f: &mut Frame<B>, app: &App) {
-    let border = Block::default()
-        .title("Pairs-rs")
-        .title_alignment(Alignment::Center)
-        .borders(Borders::ALL)
-        .border_style(Style::default().add_modifier(Modifier::DIM | Modifier::BOLD));
-    f.render_widget(border, f.size());
-
+/// Splits the title screen into the banner and one area per button. The
+/// banner is left out when there isn't room for it.
+fn title_layout(area: Rect, buttons: usize) -> (Rect, Vec<Rect>) {
+    let roomy =
+        area.width >= BANNER_SIZE.0 + 12 && area.height >= BANNER_SIZE.1 + 3 * buttons as u16 + 4;
     let chunks_y = Layout::default()
         .direction(Direction::Vertical)
-        .margin(6)
+        .horizontal_margin(if roomy { 6 } else { 1 })
+        .vertical_margin(if roomy { 2 } else { 1 })
         .constraints(
             [
-                Constraint::Length(15),
-                Constraint::Length(9),
+                Constraint::Length(if roomy { BANNER_SIZE.1 } else { 0 }),
+                Constraint::Length(3 * buttons as u16),
                 Constraint::Min(3),
             ]
             .as_ref(),
         )
-        .split(f.size());
+        .split(area);
 
     let buttons_y = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(
-            [
-                Constraint::Length(3),
-                Constraint::Length(3),
-                Constraint::Length(3),
-            ]
-            .as_ref(),
-        )
+        .constraints(vec![Constraint::Length(3); buttons])
         .split(chunks_y[1]);
 
-    let button_areas = (0..app.ui_defaults.all_buttons.len())
-        .map(|i| {
-            Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints(
-                    [
-                        Constraint::Percentage(40),
-                        Constraint::Percentage(20),
-                        Constraint::Percentage(40),
-                    ]
-                    .as_ref(),
-                )
-                .split(buttons_y[i])[1]
+    let button_areas = buttons_y
+        .iter()
+        .map(|&row| {
+            let width = (row.width / 5).max(15).min(row.width);
+            Rect::new(row.x + (row.width - width) / 2, row.y, width, row.height)
         })
         .collect_vec();
+    (chunks_y[0], button_areas)
+}
+
+/// The title button under the terminal cell `(column, row)`.
+pub fn title_button_at(area: Rect, buttons: usize, column: u16, row: u16) -> Option<usize> {
+    let (_, button_areas) = title_layout(area, buttons);
+    button_areas.iter().position(|&a| contains(a, column, row))
+}
+
+/// Smallest terminal the UI draws in.
+const MIN_SIZE: (u16, u16) = (48, 24);
+const BANNER_SIZE: (u16, u16) = (84, 10);
+
+pub fn fits(area: Rect) -> bool {
+    area.width >= MIN_SIZE.0 && area.height >= MIN_SIZE.1
+}
+
+fn too_small<B: Backend>(f: &mut Frame<B>, (width, height): (u16, u16)) {
+    let area = f.size();
+    let text = vec![
+        Spans::from(Span::styled(
+            "Terminal too small",
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(format!(
+            "Need {width}x{height}, have {}x{}.",
+            area.width, area.height
+        )),
+    ];
+    let y = area.y + area.height.saturating_sub(2) / 2;
+    let para = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(
+        para,
+        Rect::new(area.x, y, area.width, area.height - (y - area.y)),
+    );
+}
+
+/// Margin around a screen's content, as much as 6 cells when there's room for
+/// `content` rows.
+fn margin(area: Rect, content: u16) -> u16 {
+    (area.height.saturating_sub(content) / 2).clamp(1, 6)
+}
+
+fn contains(r: Rect, column: u16, row: u16) -> bool {
+    (r.left()..r.right()).contains(&column) && (r.top()..r.bottom()).contains(&row)
+}
+
+fn title<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let border = Block::default()
+        .title("Pairs-rs")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(app.palette.border().add_modifier(Modifier::BOLD));
+    f.render_widget(border, f.size());
+
+    let (banner_area, button_areas) = title_layout(f.size(), app.ui_defaults.all_buttons.len());
 
     let pair_rs_banner = "                                                                        
                           _/                                                      
@@ -74,12 +128,12 @@ _/
     let mut pair_rs_banner_txt = Text::from(pair_rs_banner);
     pair_rs_banner_txt.patch_style(
         Style::default()
-            .fg(Color::LightBlue)
+            .fg(app.palette.accent)
             .add_modifier(Modifier::BOLD),
     );
     let pair_rs_banner_para = Paragraph::new(pair_rs_banner_txt).alignment(Alignment::Center);
 
-    f.render_widget(pair_rs_banner_para, chunks_y[0]);
+    f.render_widget(pair_rs_banner_para, banner_area);
 
     app.ui_defaults
         .all_buttons
@@ -89,9 +143,9 @@ _/
         .for_each(|(i, (s, a)): (usize, (&TitleButtons, Rect))| {
             let is_selected = i as i8 == app.curr_index;
             let color = if is_selected {
-                Color::LightBlue
+                app.palette.accent
             } else {
-                Color::Gray
+                Color::Reset
             };
             let mod_ = if is_selected {
                 Modifier::BOLD
@@ -105,52 +159,45 @@ _/
                 .block(Block::default().borders(Borders::ALL).border_style(style));
             f.render_widget(para, a);
         });
-}
-
-#[allow(unused_variables)] // TODO:
-fn player_count_input<B: Backend>(f: &mut Frame<B>, app: &App) {
-    todo!()
-}
-
-#[allow(unused_variables)] // TODO:
-fn gameplay<B: Backend>(f: &mut Frame<B>, app: &App) {
-    todo!()
-}
 
-#[allow(unused_variables)] // TODO:
-fn options<B: Backend>(f: &mut Frame<B>, app: &App) {
-    todo!()
+    let area = f.size();
+    let hint = Paragraph::new(format!("Press {} for help", app.keymap.label(Action::Help)))
+        .style(Style::default().add_modifier(Modifier::DIM))
+        .alignment(Alignment::Center);
+    f.render_widget(
+        hint,
+        Rect::new(area.x + 1, area.bottom() - 2, area.width - 2, 1),
+    );
 }
 
-fn player_name_input<B: Backend>(f: &mut Frame<B>, app: &App) {
-    let block = Block::default()
-        .title("Players")
-        .title_alignment(Alignment::Center)
-        .borders(Borders::ALL)
-        .border_style(Style::default().add_modifier(Modifier::DIM));
-    f.render_widget(block, f.size());
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(6)
-        .constraints(
-            [
-                Constraint::Length(5),
-                Constraint::Length(3),
-                Constraint::Min(1),
-            ]
-            .as_ref(),
-        )
-        .split(f.size());
-
+fn text_input<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    title: &str,
+    back_hint: &str,
+    submit_hint: &str,
+    chunks: &[Rect],
+) {
+    let key = |action| app.keymap.label(action);
     let (msg, style) = match app.input_mode {
         InputMode::Normal => (
             vec![
                 Span::raw("Press "),
-                Span::styled("q", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(" to go back, "),
-                Span::styled("e", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(" to start typing."),
+                Span::styled(
+                    key(Action::Back),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!(" to {back_hint}, ")),
+                Span::styled(
+                    key(Action::Edit),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to start typing, "),
+                Span::styled(
+                    key(Action::Confirm),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!("{submit_hint}.")),
             ],
             Style::default().add_modifier(Modifier::RAPID_BLINK),
         ),
@@ -173,9 +220,9 @@ fn player_name_input<B: Backend>(f: &mut Frame<B>, app: &App) {
     let input = Paragraph::new(app.input.as_ref())
         .style(match app.input_mode {
             InputMode::Normal => Style::default(),
-            InputMode::Action1 => Style::default().fg(Color::LightBlue),
+            InputMode::Action1 => Style::default().fg(app.palette.accent),
         })
-        .block(Block::default().borders(Borders::ALL).title("Input"));
+        .block(Block::default().borders(Borders::ALL).title(title));
     f.render_widget(input, chunks[1]);
     match app.input_mode {
         InputMode::Normal => {}
@@ -183,22 +230,801 @@ fn player_name_input<B: Backend>(f: &mut Frame<B>, app: &App) {
             f.set_cursor(chunks[1].x + app.input.width() as u16 + 1, chunks[1].y + 1)
         }
     }
+}
+
+fn player_count_input<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let block = Block::default()
+        .title("Players")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(app.palette.border());
+    f.render_widget(block, f.size());
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(margin(f.size(), 9))
+        .constraints(
+            [
+                Constraint::Length(5),
+                Constraint::Length(3),
+                Constraint::Min(1),
+            ]
+            .as_ref(),
+        )
+        .split(f.size());
+
+    text_input(
+        f,
+        app,
+        "Player Count",
+        "go back",
+        " to continue",
+        &chunks[..2],
+    );
+
+    let (min, max) = app.ui_defaults.player_count_bounds;
+    let hint = Paragraph::new(format!("Enter a number of players from {min} to {max}."))
+        .style(Style::default().add_modifier(Modifier::DIM));
+    f.render_widget(hint, chunks[2]);
+}
+
+fn join_input<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let block = Block::default()
+        .title("Join")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(app.palette.border());
+    f.render_widget(block, f.size());
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(margin(f.size(), 9))
+        .constraints(
+            [
+                Constraint::Length(5),
+                Constraint::Length(3),
+                Constraint::Min(1),
+            ]
+            .as_ref(),
+        )
+        .split(f.size());
+
+    text_input(
+        f,
+        app,
+        "name@host[:port]",
+        "go back",
+        " to join",
+        &chunks[..2],
+    );
+
+    let hint = Paragraph::new(format!(
+        "Use the name the host gave your seat. The port defaults to {}.",
+        net::DEFAULT_PORT
+    ))
+    .style(Style::default().add_modifier(Modifier::DIM));
+    f.render_widget(hint, chunks[2]);
+}
+
+/// Card tile sizes to try, largest first. Tiles under 3 rows tall have no
+/// border.
+const TILE_SIZES: [(u16, u16); 4] = [card::FULL_SIZE, (7, 3), (5, 3), (4, 1)];
+
+/// [`TILE_SIZES`] for `board`, widened where its widest tile face wouldn't
+/// fit.
+fn tile_sizes(board: &Board) -> [(u16, u16); 4] {
+    let face = board.tile_width() as u16;
+    TILE_SIZES.map(|(w, h)| {
+        let padding = if h >= 3 { 2 } else { 1 };
+        (w.max(face + padding), h)
+    })
+}
+
+/// Width of the scoreboard column beside the board.
+const SIDE_WIDTH: u16 = 28;
+
+/// Splits a game screen into the board, the status bar and the scoreboard.
+/// The scoreboard is a column beside the board, or a strip under the status
+/// bar when `stacked`.
+fn game_layout(area: Rect, game: &Game, stacked: bool) -> (Rect, Rect, Rect) {
+    if stacked {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(2)
+            .constraints(
+                [
+                    Constraint::Min(1),
+                    Constraint::Length(3),
+                    Constraint::Length(game.players().len() as u16 + 2),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        return (chunks[0], chunks[1], chunks[2]);
+    }
+    let chunks_x = Layout::default()
+        .direction(Direction::Horizontal)
+        .margin(2)
+        .constraints([Constraint::Min(1), Constraint::Length(SIDE_WIDTH)].as_ref())
+        .split(area);
+    let chunks_y = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(3)].as_ref())
+        .split(chunks_x[0]);
+    (chunks_y[0], chunks_y[1], chunks_x[1])
+}
+
+/// Slots on the board as dealt, leaving out the padding after the last card.
+fn dealt_slots(game: &Game) -> usize {
+    match game.size() {
+        0 => game.board().rows() * game.board().cols(),
+        size => size as usize * 2,
+    }
+}
+
+/// How a board is drawn on screen. Cards keep the positions they were dealt
+/// at, but are wrapped in reading order into as many columns as fit.
+#[derive(Clone, Copy)]
+pub struct BoardView {
+    area: Rect,
+    tile: (u16, u16),
+    cols: usize,
+    dealt_cols: usize,
+    slots: usize,
+}
+
+impl BoardView {
+    /// Lays `game`'s board out in `area` with tiles of `tile` size, in the
+    /// dealt columns if they fit and otherwise in whatever fits best.
+    fn fit(area: Rect, game: &Game, (w, h): (u16, u16)) -> Option<Self> {
+        let dealt_cols = game.board().cols();
+        let slots = dealt_slots(game);
+        let (max_cols, max_rows) = ((area.width / w) as usize, (area.height / h) as usize);
+        let cols = if dealt_cols <= max_cols && slots.div_ceil(dealt_cols) <= max_rows {
+            dealt_cols
+        } else {
+            utils::grid_to_fit(slots, max_cols, max_rows)?
+        };
+        Some(Self {
+            area,
+            tile: (w, h),
+            cols,
+            dealt_cols,
+            slots,
+        })
+    }
+
+    fn rows(&self) -> usize {
+        self.slots.div_ceil(self.cols)
+    }
+
+    /// The screen row and column `pos` is drawn at.
+    fn cell(&self, (x, y): Pos) -> (usize, usize) {
+        let i = x as usize * self.dealt_cols + y as usize;
+        (i / self.cols, i % self.cols)
+    }
+
+    /// The position drawn at screen row and column `(row, col)`, if any.
+    fn pos(&self, (row, col): (usize, usize)) -> Option<Pos> {
+        let i = row * self.cols + col;
+        (col < self.cols && i < self.slots)
+            .then(|| ((i / self.dealt_cols) as u8, (i % self.dealt_cols) as u8))
+    }
+
+    /// The top-left corner of the board, centered in its area.
+    fn origin(&self) -> (u16, u16) {
+        let (w, h) = self.tile;
+        let (board_w, board_h) = (self.cols as u16 * w, self.rows() as u16 * h);
+        (
+            self.area.x + self.area.width.saturating_sub(board_w) / 2,
+            self.area.y + self.area.height.saturating_sub(board_h) / 2,
+        )
+    }
+
+    /// Where the card at `pos` is drawn.
+    fn rect(&self, pos: Pos) -> Rect {
+        let (origin_x, origin_y) = self.origin();
+        let (row, col) = self.cell(pos);
+        let (w, h) = self.tile;
+        Rect::new(origin_x + col as u16 * w, origin_y + row as u16 * h, w, h)
+    }
+
+    /// The position under the terminal cell `(column, row)`.
+    fn at(&self, column: u16, row: u16) -> Option<Pos> {
+        let (origin_x, origin_y) = self.origin();
+        let (w, h) = self.tile;
+        let col = column.checked_sub(origin_x)? / w;
+        let row = row.checked_sub(origin_y)? / h;
+        if row as usize >= self.rows() {
+            return None;
+        }
+        self.pos((row as usize, col as usize))
+    }
+
+    /// The position `(dx, dy)` rows and columns away from `pos` on screen,
+    /// stopping at the edges. Stays at `pos` if there's no card there.
+    pub fn step(&self, pos: Pos, dx: i8, dy: i8) -> Pos {
+        let (row, col) = self.cell(pos);
+        let row = (row as isize + dx as isize).clamp(0, self.rows() as isize - 1);
+        let col = (col as isize + dy as isize).clamp(0, self.cols as isize - 1);
+        self.pos((row as usize, col as usize)).unwrap_or(pos)
+    }
+}
+
+/// A game screen laid out for a terminal.
+struct GameLayout {
+    board: BoardView,
+    status: Rect,
+    side: Rect,
+    stacked: bool,
+}
+
+/// Lays out a game screen in `area` with tiles as large as possible. The
+/// scoreboard moves under the board when that makes room for larger tiles.
+fn fit_game(area: Rect, game: &Game) -> Option<GameLayout> {
+    tile_sizes(game.board())
+        .into_iter()
+        .cartesian_product([false, true])
+        .find_map(|(tile, stacked)| {
+            let (board_area, status, side) = game_layout(area, game, stacked);
+            let board = BoardView::fit(board_area, game, tile)?;
+            Some(GameLayout {
+                board,
+                status,
+                side,
+                stacked,
+            })
+        })
+}
+
+/// How `game`'s board is drawn in a terminal of size `area`, if it fits.
+pub fn board_view(area: Rect, game: &Game) -> Option<BoardView> {
+    fit_game(area, game).map(|layout| layout.board)
+}
+
+/// Terminal size needed to show `game` with the smallest tiles, in the
+/// layout closest in shape to `area`.
+fn game_min_size(area: Rect, game: &Game) -> (u16, u16) {
+    let (w, h) = tile_sizes(game.board())[TILE_SIZES.len() - 1];
+    let slots = dealt_slots(game);
+    let huge = Rect::new(0, 0, u16::MAX / 2, u16::MAX / 2);
+    [false, true]
+        .into_iter()
+        .cartesian_product(1..=slots)
+        .filter(|&(_, cols)| slots.div_ceil(cols) <= utils::MAX_ROWS)
+        .map(|(stacked, cols)| {
+            let (board, ..) = game_layout(huge, game, stacked);
+            let (margin_x, margin_y) = (huge.width - board.width, huge.height - board.height);
+            let rows = slots.div_ceil(cols) as u16;
+            (
+                (cols as u16 * w + margin_x).max(MIN_SIZE.0),
+                (rows * h + margin_y).max(MIN_SIZE.1),
+            )
+        })
+        .min_by(|&(a_w, a_h), &(b_w, b_h)| {
+            let scale = |w: u16, h: u16| {
+                (w as f64 / area.width.max(1) as f64).max(h as f64 / area.height.max(1) as f64)
+            };
+            scale(a_w, a_h)
+                .total_cmp(&scale(b_w, b_h))
+                .then((a_w as u32 * a_h as u32).cmp(&(b_w as u32 * b_h as u32)))
+        })
+        .unwrap_or(MIN_SIZE)
+}
+
+/// The card position under the terminal cell `(column, row)` on a game screen.
+pub fn tile_at(area: Rect, game: &Game, column: u16, row: u16) -> Option<Pos> {
+    board_view(area, game)?.at(column, row)
+}
+
+/// Draws a game in progress: the board, a status bar below it and the
+/// scoreboard. Shared by live games and the replay viewer.
+fn game_view<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    game: &Game,
+    title: &str,
+    cursor: Option<Pos>,
+    status: Paragraph,
+    timer: Vec<Spans>,
+) {
+    let border = Block::default()
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(app.palette.border().add_modifier(Modifier::BOLD));
+    f.render_widget(border, f.size());
+
+    let Some(GameLayout {
+        board: view,
+        status: status_area,
+        side: side_area,
+        stacked,
+    }) = fit_game(f.size(), game)
+    else {
+        too_small(f, game_min_size(f.size(), game));
+        return;
+    };
+    for (x, row) in game.board().iter().enumerate() {
+        for (y, slot) in row.iter().enumerate() {
+            let pos = (x as u8, y as u8);
+            let tile = CardTile::new(slot.as_ref(), app.settings.card_back, &app.palette)
+                .selected(cursor == Some(pos))
+                .turned(app.turned(pos));
+            f.render_widget(tile, view.rect(pos));
+        }
+    }
+
+    f.render_widget(status, status_area);
+
+    let scores: Vec<ListItem> = game
+        .players()
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let is_current = i == game.turn() && !game.is_over();
+            let marker = if is_current { "▶ " } else { "  " };
+            let style = if is_current {
+                Style::default()
+                    .fg(app.palette.accent)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let tag = match &app.link {
+                Some(Link::Host(host)) if host.is_remote(i) => " (remote)",
+                Some(Link::Client(client)) if client.player() == Some(i) => " (you)",
+                _ => "",
+            };
+            ListItem::new(Spans::from(vec![
+                Span::styled(format!("{marker}{}", p.name), style),
+                Span::styled(tag, Style::default().add_modifier(Modifier::DIM)),
+                Span::raw(format!(" - {} pairs", p.pairs())),
+            ]))
+        })
+        .collect();
+    let scoreboard =
+        List::new(scores).block(Block::default().borders(Borders::ALL).title("Scoreboard"));
+    if timer.is_empty() {
+        f.render_widget(scoreboard, side_area);
+        return;
+    }
+    let side = if stacked {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(1), Constraint::Length(SIDE_WIDTH)].as_ref())
+            .split(side_area)
+    } else {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(game.players().len() as u16 + 2),
+                    Constraint::Min(3),
+                ]
+                .as_ref(),
+            )
+            .split(side_area)
+    };
+    f.render_widget(scoreboard, side[0]);
+    let timer = Paragraph::new(timer).block(Block::default().borders(Borders::ALL).title("Timer"));
+    f.render_widget(timer, side[1]);
+}
+
+fn status_bar<'a>(status: Spans<'a>, title: &'a str) -> Paragraph<'a> {
+    Paragraph::new(status)
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL).title(title))
+}
+
+/// Lines for the timer panel, depending on the timer mode.
+fn timer(app: &App) -> Vec<Spans<'static>> {
+    let Some(clock) = &app.clock else {
+        return vec![];
+    };
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let time = |d: Duration| stats::format_time(d.as_millis() as u64);
+    match app.game_settings.timer {
+        TimerMode::Off => vec![],
+        TimerMode::Countdown => {
+            let left = app
+                .game_settings
+                .time_limit()
+                .saturating_sub(clock.elapsed());
+            vec![Spans::from(vec![
+                Span::raw("Time left "),
+                Span::styled(time(left), bold),
+            ])]
+        }
+        TimerMode::TurnLimit => {
+            let left = app
+                .game_settings
+                .turn_limit()
+                .saturating_sub(clock.turn_elapsed());
+            let style = if left <= Duration::from_secs(3) {
+                bold.fg(app.palette.err)
+            } else {
+                bold
+            };
+            vec![Spans::from(vec![
+                Span::raw("Turn time left "),
+                Span::styled(time(left), style),
+            ])]
+        }
+        TimerMode::Speedrun => {
+            let mut lines = vec![Spans::from(vec![
+                Span::raw("Time "),
+                Span::styled(time(clock.elapsed()), bold),
+            ])];
+            let splits = clock.splits();
+            lines.extend(splits.iter().enumerate().rev().map(|(i, &split)| {
+                let prev = i.checked_sub(1).map_or(Duration::ZERO, |p| splits[p]);
+                Spans::from(vec![
+                    Span::raw(format!("Pair {:<3} {} ", i + 1, time(split))),
+                    Span::styled(
+                        format!("+{}", time(split - prev)),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ),
+                ])
+            }));
+            lines
+        }
+    }
+}
+
+fn gameplay<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let title = match &app.link {
+        Some(Link::Host(host)) => format!("Pairs-rs - hosting on port {}", host.port()),
+        Some(Link::Client(_)) => "Pairs-rs - joined".to_owned(),
+        None => "Pairs-rs".to_owned(),
+    };
+    let Some(game) = &app.game else {
+        let waiting = Paragraph::new(format!(
+            "Waiting for host... Press {} to leave.",
+            app.keymap.label(Action::Back)
+        ))
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL),
+        );
+        f.render_widget(waiting, f.size());
+        return;
+    };
+
+    let current = game.current_player();
+    let key = |action| {
+        Span::styled(
+            app.keymap.label(action),
+            Style::default().add_modifier(Modifier::BOLD),
+        )
+    };
+    let status = match game.phase() {
+        Phase::Over => {
+            let winners = game.winners().iter().map(|p| p.name.as_str()).join(", ");
+            vec![
+                Span::styled("Game over! ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(format!("Winner: {winners}. Press ")),
+                key(Action::Confirm),
+                Span::raw(" to return to title, "),
+                key(Action::WatchReplay),
+                Span::raw(" to watch the replay."),
+            ]
+        }
+        Phase::Mismatch(..) if app.is_local_turn() => vec![
+            Span::raw("No match. Press any key or click to "),
+            Span::styled("flip back", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("."),
+        ],
+        Phase::Mismatch(..) => vec![Span::raw("No match.")],
+        _ if !app.is_local_turn() => vec![
+            Span::styled(
+                current.name.as_str(),
+                Style::default()
+                    .fg(app.palette.accent)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("'s turn. Press "),
+            key(Action::Back),
+            Span::raw(" to quit."),
+        ],
+        _ => {
+            let mut spans = vec![
+                Span::styled(
+                    current.name.as_str(),
+                    Style::default()
+                        .fg(app.palette.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("'s turn. Press "),
+                key(Action::Flip),
+                Span::raw(" or click to flip, "),
+            ];
+            if game.is_practice() && app.link.is_none() {
+                spans.extend([
+                    key(Action::Undo),
+                    Span::raw("/"),
+                    key(Action::Redo),
+                    Span::raw(" to undo/redo, "),
+                ]);
+            }
+            spans.extend([
+                key(Action::Back),
+                Span::raw(" to quit, "),
+                key(Action::Help),
+                Span::raw(" for help."),
+            ]);
+            spans
+        }
+    };
+    game_view(
+        f,
+        app,
+        game,
+        &title,
+        Some(app.ui_var.cursor),
+        status_bar(Spans::from(status), ""),
+        timer(app),
+    );
+}
+
+fn replay<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let Some(viewer) = &app.viewer else { return };
+    let events = viewer.replay().events();
+    let last = viewer.step().checked_sub(1).and_then(|i| events.get(i));
+    let cursor = match last.map(|e| e.kind) {
+        Some(EventKind::Reveal { pos, .. }) => Some(pos),
+        _ => None,
+    };
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut status = vec![
+        Span::styled(
+            format!("Move {}/{} ", viewer.step(), viewer.last_step()),
+            bold.fg(app.palette.accent),
+        ),
+        Span::raw(format!(
+            "{} {}x  ",
+            if viewer.is_playing() { "▶" } else { "⏸" },
+            viewer.speed()
+        )),
+    ];
+    let key = |action| Span::styled(app.keymap.label(action), bold);
+    match app.input_mode {
+        InputMode::Normal => status.extend([
+            key(Action::Left),
+            Span::raw("/"),
+            key(Action::Right),
+            Span::raw(" step, "),
+            key(Action::Pause),
+            Span::raw(" play, "),
+            key(Action::Faster),
+            Span::raw("/"),
+            key(Action::Slower),
+            Span::raw(" speed, "),
+            key(Action::Jump),
+            Span::raw(" jump, "),
+            key(Action::Back),
+            Span::raw(" back"),
+        ]),
+        InputMode::Action1 => status.extend([
+            Span::raw("Jump to move: "),
+            Span::styled(format!("{}_", app.input), bold),
+        ]),
+    }
+    let status_title = last
+        .map(|e| viewer.replay().describe(e))
+        .unwrap_or_else(|| "Start".to_owned());
+    game_view(
+        f,
+        app,
+        viewer.game(),
+        "Pairs-rs - replay",
+        cursor,
+        status_bar(Spans::from(status), &status_title),
+        vec![],
+    );
+}
+
+fn leaderboard<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let Some(stats) = &app.stats else { return };
+    let accent = app.palette.accent;
+    let header_style = Style::default().fg(accent).add_modifier(Modifier::BOLD);
+    let block = Block::default()
+        .title("Leaderboard")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(app.palette.border());
+    f.render_widget(block, f.size());
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints(
+            [
+                Constraint::Length(2),
+                Constraint::Min(5),
+                Constraint::Length(12),
+            ]
+            .as_ref(),
+        )
+        .split(f.size());
+
+    let help_message = Paragraph::new(Spans::from(vec![
+        Span::raw(format!("{} games recorded. Press ", stats.games.len())),
+        Span::styled(
+            app.keymap.label(Action::Back),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(" to go back."),
+    ]));
+    f.render_widget(help_message, chunks[0]);
+
+    let rows = stats.players().into_iter().map(|p| {
+        let name = if p.is_bot {
+            format!("{} (bot)", p.name)
+        } else {
+            p.name.clone()
+        };
+        Row::new(vec![
+            Cell::from(name),
+            Cell::from(p.played.to_string()),
+            Cell::from(p.won.to_string()),
+            Cell::from(format!("{:.0}%", p.win_rate() * 100.0)),
+            Cell::from(format!("{:.0}%", p.accuracy() * 100.0)),
+        ])
+    });
+    let players = Table::new(rows)
+        .header(
+            Row::new(vec!["Player", "Played", "Won", "Win rate", "Accuracy"]).style(header_style),
+        )
+        .block(Block::default().borders(Borders::ALL).title("Players"))
+        .widths(&[
+            Constraint::Percentage(40),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+        ]);
+    f.render_widget(players, chunks[1]);
+
+    let rows = stats.best_times().into_iter().map(|t| {
+        Row::new(vec![
+            Cell::from(format!("{} pairs", t.board_size)),
+            Cell::from(stats::format_time(t.time_ms)),
+            Cell::from(t.name),
+            Cell::from(t.seed.to_string()),
+        ])
+    });
+    let times = Table::new(rows)
+        .header(Row::new(vec!["Board", "Best time", "Player", "Seed"]).style(header_style))
+        .block(Block::default().borders(Borders::ALL).title("Best times"))
+        .widths(&[
+            Constraint::Percentage(20),
+            Constraint::Percentage(20),
+            Constraint::Percentage(35),
+            Constraint::Percentage(25),
+        ]);
+    f.render_widget(times, chunks[2]);
+}
+
+fn options<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let accent = app.palette.accent;
+    let block = Block::default()
+        .title("Options")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(app.palette.border());
+    f.render_widget(block, f.size());
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(margin(f.size(), 3 + SettingsField::ALL.len() as u16 + 2))
+        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+        .split(f.size());
+
+    let key = |action| {
+        Span::styled(
+            app.keymap.label(action),
+            Style::default().add_modifier(Modifier::BOLD),
+        )
+    };
+    let help_message = Paragraph::new(Spans::from(vec![
+        Span::raw("Press "),
+        key(Action::Left),
+        Span::raw("/"),
+        key(Action::Right),
+        Span::raw(" to change, "),
+        key(Action::Reset),
+        Span::raw(" to reset, "),
+        key(Action::Back),
+        Span::raw(" to save and go back."),
+    ]));
+    f.render_widget(help_message, chunks[0]);
+
+    let items: Vec<ListItem> = SettingsField::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, &field)| {
+            let is_selected = i as i8 == app.curr_index;
+            let style = if is_selected {
+                Style::default().fg(accent).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(Spans::from(vec![
+                Span::styled(format!("{:<20}", field.to_string()), style),
+                Span::styled(format!("< {} >", app.settings.value_of(field)), style),
+            ]))
+        })
+        .collect();
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title("Settings"));
+    f.render_widget(list, chunks[1]);
+}
+
+fn player_name_input<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let block = Block::default()
+        .title("Players")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(app.palette.border());
+    f.render_widget(block, f.size());
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(margin(f.size(), 8 + app.ui_var.player_count as u16 + 2))
+        .constraints(
+            [
+                Constraint::Length(5),
+                Constraint::Length(3),
+                Constraint::Min(1),
+            ]
+            .as_ref(),
+        )
+        .split(f.size());
+
+    let kind = match app.ui_var.next_bot {
+        Some(difficulty) => format!("Bot, {difficulty}, Tab to change, ←/→ for memory"),
+        None => "Human, Tab to change".to_owned(),
+    };
+    let title = format!(
+        "Name ({}/{}) - {kind}",
+        app.ui_var.player_names.len(),
+        app.ui_var.player_count
+    );
+    text_input(
+        f,
+        app,
+        &title,
+        "go back",
+        " to start the game",
+        &chunks[..2],
+    );
 
     let player_names: Vec<ListItem> = app
         .ui_var
         .player_names
         .iter()
+        .zip(&app.ui_var.player_bots)
         .enumerate()
-        .map(|(i, m)| {
-            let content = Spans::from(vec![
+        .map(|(i, (m, bot))| {
+            let mut spans = vec![
                 Span::styled(
                     format!("P{}: ", i + 1),
                     Style::default()
-                        .fg(Color::LightBlue)
+                        .fg(app.palette.accent)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(m),
-            ]);
+            ];
+            if let Some(difficulty) = bot {
+                spans.push(Span::styled(
+                    format!(" (Bot, {difficulty})"),
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+            }
+            let content = Spans::from(spans);
             ListItem::new(content)
         })
         .collect();
@@ -211,11 +1037,30 @@ fn player_name_input<B: Backend>(f: &mut Frame<B>, app: &App) {
 }
 
 pub fn ui<B: Backend>(f: &mut Frame<B>, app: &App) {
+    if !fits(f.size()) {
+        too_small(f, MIN_SIZE);
+        return;
+    }
+    match &app.curr_screen {
+        Screen::Title => title(f, app),
+        Screen::PlayerCountInput => player_count_input(f, app),
+        Screen::PlayerNameInput => player_name_input(f, app),
+        Screen::JoinInput => join_input(f, app),
+        Screen::Gameplay => gameplay(f, app),
+        Screen::Replay => replay(f, app),
+        Screen::Leaderboard => leaderboard(f, app),
+        Screen::Options => options(f, app),
+    }
+
+    if app.ui_var.help {
+        help(f, app);
+    }
+
     if let Some(PopupMsg { msg, severity }) = &app.popup {
         let (title, color) = match severity {
-            PopupSeverity::Info => ("Hint", Color::LightBlue),
-            PopupSeverity::Warn => ("Warning", Color::Yellow),
-            PopupSeverity::Err => ("Error", Color::Red),
+            PopupSeverity::Info => ("Hint", app.palette.info),
+            PopupSeverity::Warn => ("Warning", app.palette.warn),
+            PopupSeverity::Err => ("Error", app.palette.err),
         };
 
         let block = Block::default()
@@ -223,43 +1068,159 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &App) {
             .title_alignment(Alignment::Center)
             .borders(Borders::ALL)
             .border_style(Style::default().fg(color));
-        let area = centered_rect(20, 20, f.size());
-        let paragraph = Paragraph::new(msg.as_str()).block(block);
-        // f.render_widget(Clear, area);
+        let area = popup_rect(f.size());
+        let paragraph = Paragraph::new(msg.as_str())
+            .wrap(Wrap { trim: true })
+            .block(block);
+        f.render_widget(Clear, area);
         f.render_widget(paragraph, area);
     }
+}
 
-    match &app.curr_screen {
-        Screen::Title => title(f, app),
-        Screen::PlayerCountInput => player_count_input(f, app),
-        Screen::PlayerNameInput => player_name_input(f, app),
-        Screen::Gameplay => gameplay(f, app),
-        Screen::Options => options(f, app),
-    }
+/// Every action that can be used on the current screen and the keys bound
+/// to it.
+fn help<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let context = app.context();
+    let rows = Action::ALL
+        .into_iter()
+        .filter(|a| a.contexts().contains(&context))
+        .map(|a| {
+            Row::new([
+                Cell::from(app.keymap.labels(a)).style(
+                    Style::default()
+                        .fg(app.palette.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Cell::from(a.to_string()),
+            ])
+        })
+        .collect_vec();
+    let title = match context {
+        Context::Menu => "Keys",
+        Context::Game => "Keys - game",
+        Context::GameOver => "Keys - game over",
+        Context::Replay => "Keys - replay",
+    };
+    let area = f.size();
+    let width = 48.min(area.width);
+    let height = (rows.len() as u16 + 3).min(area.height);
+    let area = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    let table = Table::new(rows)
+        .widths(&[Constraint::Length(18), Constraint::Min(10)])
+        .column_spacing(2)
+        .block(
+            Block::default()
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(app.palette.info)),
+        );
+    f.render_widget(Clear, area);
+    f.render_widget(table, area);
+    f.render_widget(
+        Paragraph::new("Press any key to close")
+            .style(Style::default().add_modifier(Modifier::DIM))
+            .alignment(Alignment::Center),
+        Rect::new(area.x + 1, area.bottom() - 2, area.width - 2, 1),
+    );
 }
 
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(
-            [
-                Constraint::Percentage((100 - percent_y) / 2),
-                Constraint::Percentage(percent_y),
-                Constraint::Percentage((100 - percent_y) / 2),
-            ]
-            .as_ref(),
-        )
-        .split(r);
+/// A fifth of the screen each way, but never too small to read.
+fn popup_rect(r: Rect) -> Rect {
+    let width = (r.width / 5).max(36).min(r.width);
+    let height = (r.height / 5).max(6).min(r.height);
+    Rect::new(
+        r.x + (r.width - width) / 2,
+        r.y + (r.height - height) / 2,
+        width,
+        height,
+    )
+}
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(
-            [
-                Constraint::Percentage((100 - percent_x) / 2),
-                Constraint::Percentage(percent_x),
-                Constraint::Percentage((100 - percent_x) / 2),
-            ]
-            .as_ref(),
-        )
-        .split(popup_layout[1])[1]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::game::fixtures::card_game;
+
+    fn area(width: u16, height: u16) -> Rect {
+        Rect::new(0, 0, width, height)
+    }
+
+    #[test]
+    fn narrow_terminals_stack_the_scoreboard() {
+        let game = card_game(26);
+        let layout = fit_game(area(60, 40), &game).unwrap();
+        assert!(layout.stacked);
+        assert!(layout.side.y >= layout.status.bottom());
+        assert_eq!(layout.side.width, 56);
+
+        let layout = fit_game(area(120, 40), &game).unwrap();
+        assert!(!layout.stacked);
+        assert_eq!(layout.side.width, SIDE_WIDTH);
+    }
+
+    #[test]
+    fn every_card_is_drawn_once_inside_the_board() {
+        for (pairs, width, height) in [(26, 60, 40), (26, 48, 24), (26, 120, 40), (5, 48, 24)] {
+            let game = card_game(pairs);
+            let view = board_view(area(width, height), &game).unwrap();
+            let rects = game
+                .board()
+                .slots()
+                .map(|(pos, _)| (pos, view.rect(pos)))
+                .collect_vec();
+            for ((pos, rect), (other, other_rect)) in rects.into_iter().tuple_combinations() {
+                assert!(!rect.intersects(other_rect), "{pos:?} {other:?}");
+            }
+            for (pos, _) in game.board().slots() {
+                let rect = view.rect(pos);
+                assert_eq!(view.area.union(rect), view.area, "{pos:?}");
+                assert_eq!(view.at(rect.x, rect.y), Some(pos));
+                assert_eq!(view.at(rect.right() - 1, rect.bottom() - 1), Some(pos));
+            }
+        }
+    }
+
+    #[test]
+    fn dealt_layout_is_kept_where_it_fits() {
+        let game = card_game(26);
+        let view = board_view(area(200, 100), &game).unwrap();
+        assert_eq!(view.cols, game.board().cols());
+        assert_eq!(view.cell((1, 2)), (1, 2));
+
+        let view = board_view(area(60, 40), &game).unwrap();
+        assert_ne!(view.cols, game.board().cols());
+        for (pos, _) in game.board().slots() {
+            assert_eq!(view.pos(view.cell(pos)), Some(pos));
+        }
+    }
+
+    #[test]
+    fn cursor_steps_across_the_screen_layout() {
+        let game = card_game(26);
+        let view = board_view(area(60, 40), &game).unwrap();
+        let last_col = (0, view.cols - 1);
+        let end_of_row = view.pos(last_col).unwrap();
+        assert_eq!(view.step(end_of_row, 0, 1), end_of_row);
+        assert_eq!(view.cell(view.step(end_of_row, 1, 0)), (1, view.cols - 1));
+        assert_eq!(view.step((0, 0), -1, -1), (0, 0));
+        let last = view.pos(((view.slots - 1) / view.cols, (view.slots - 1) % view.cols));
+        assert_eq!(view.step(last.unwrap(), 1, 0), last.unwrap());
+    }
+
+    #[test]
+    fn min_size_fits_the_board() {
+        for pairs in [4, 26] {
+            let game = card_game(pairs);
+            for (width, height) in [(48, 24), (60, 40), (200, 30), (50, 100)] {
+                let (w, h) = game_min_size(area(width, height), &game);
+                assert!(fit_game(area(w, h), &game).is_some(), "{pairs} {w}x{h}");
+            }
+        }
+    }
 }
@@ -0,0 +1,94 @@
+use crate::core::{game::Game, replay::Replay};
+use std::time::{Duration, Instant};
+
+pub const SPEEDS: [f64; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
+/// Longest pause played back between two moves, so a player who walked away
+/// mid-game doesn't stall the replay.
+const MAX_GAP: Duration = Duration::from_secs(3);
+
+pub struct ReplayViewer {
+    replay: Replay,
+    step: usize,
+    game: Game,
+    playing: bool,
+    speed: usize,
+    stepped_at: Instant,
+}
+
+impl ReplayViewer {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            game: replay.start().clone(),
+            replay,
+            step: 0,
+            playing: false,
+            speed: 2,
+            stepped_at: Instant::now(),
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Number of events applied so far.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    pub fn last_step(&self) -> usize {
+        self.replay.events().len()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn speed(&self) -> f64 {
+        SPEEDS[self.speed]
+    }
+
+    pub fn seek(&mut self, step: usize) {
+        self.step = step.min(self.last_step());
+        self.game = self.replay.state_at(self.step);
+        self.stepped_at = Instant::now();
+        if self.step == self.last_step() {
+            self.playing = false;
+        }
+    }
+
+    pub fn forward(&mut self) {
+        self.seek(self.step + 1);
+    }
+
+    pub fn back(&mut self) {
+        self.seek(self.step.saturating_sub(1));
+    }
+
+    pub fn toggle_play(&mut self) {
+        if self.step == self.last_step() {
+            self.seek(0);
+        }
+        self.playing = !self.playing;
+        self.stepped_at = Instant::now();
+    }
+
+    pub fn change_speed(&mut self, step: i8) {
+        self.speed = (self.speed as i8 + step).clamp(0, SPEEDS.len() as i8 - 1) as usize;
+    }
+
+    /// How long until the next event should play, while playing.
+    pub fn wait(&self) -> Option<Duration> {
+        if !self.playing {
+            return None;
+        }
+        let gap = self.replay.gap_before(self.step).min(MAX_GAP);
+        let gap = gap.div_f64(self.speed());
+        Some(gap.saturating_sub(self.stepped_at.elapsed()))
+    }
+}
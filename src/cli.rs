@@ -0,0 +1,201 @@
+use crate::{
+    app::{save, settings::Settings, stats::Stats},
+    core::{ai::Difficulty, game::Game, rules::PairRule, tiles::Tile, utils, Player},
+    line, start_ui,
+};
+use clap::{Args, Parser, Subcommand};
+use itertools::Itertools;
+use std::{
+    error::Error,
+    io::{self, IsTerminal},
+    path::PathBuf,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "pairs",
+    version,
+    about = "The card game Pairs, in your terminal."
+)]
+pub struct Cli {
+    /// Use plain lines of text instead of the full-screen interface. This is
+    /// the default when input or output isn't a terminal
+    #[arg(long, global = true)]
+    pub plain: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Start a game right away, skipping the setup screens
+    Play(DealArgs),
+    /// Deal a board and print where every pair is
+    Solve(DealArgs),
+    /// Watch a recorded game
+    Replay { file: PathBuf },
+    /// Show win rates and best times
+    Stats,
+}
+
+const DEFAULT_PLAYER: &str = "Player";
+
+/// Anything not given here is taken from the saved settings.
+#[derive(Args)]
+pub struct DealArgs {
+    /// Number of pairs on the board
+    #[arg(long)]
+    pub pairs: Option<u8>,
+    /// Comma-separated player names; add `:easy`, `:medium`, `:hard`,
+    /// `:perfect` or a memory percentage like `:75` to a name to make that
+    /// player a bot
+    #[arg(long, value_delimiter = ',', default_value = DEFAULT_PLAYER)]
+    pub players: Vec<String>,
+    /// Seed for the deal; the same seed always deals the same board
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+impl Default for DealArgs {
+    fn default() -> Self {
+        Self {
+            pairs: None,
+            players: vec![DEFAULT_PLAYER.to_owned()],
+            seed: None,
+        }
+    }
+}
+
+fn parse_player(arg: &str) -> Result<Player, String> {
+    let (name, bot) = match arg.rsplit_once(':') {
+        Some((name, difficulty)) => {
+            let memory = difficulty
+                .parse::<u8>()
+                .ok()
+                .filter(|&m| m <= 100)
+                .map(Difficulty::Custom);
+            let difficulty = Difficulty::ALL
+                .into_iter()
+                .find(|d| d.to_string().eq_ignore_ascii_case(difficulty))
+                .or(memory)
+                .ok_or_else(|| format!("unknown bot difficulty \"{difficulty}\""))?;
+            (name, Some(difficulty))
+        }
+        None => (arg, None),
+    };
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("player names can't be empty".into());
+    }
+    Ok(match bot {
+        Some(difficulty) => Player::bot(name.to_owned(), difficulty),
+        None => Player::new(name.to_owned()),
+    })
+}
+
+impl DealArgs {
+    fn deal(&self, settings: &Settings) -> Result<Game, Box<dyn Error>> {
+        let players = self
+            .players
+            .iter()
+            .map(|p| parse_player(p))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(dup) = players.iter().map(|p| &p.name).duplicates().next() {
+            return Err(format!("name \"{dup}\" is used twice").into());
+        }
+        let deck = settings.paired_deck()?;
+        Ok(Game::new(
+            &deck,
+            self.pairs.unwrap_or(settings.board_size),
+            players,
+            settings.pair_rule,
+            self.seed.unwrap_or_else(|| settings.seed_mode.seed()),
+        )?)
+    }
+}
+
+fn solve(game: &Game) {
+    let board = game.board();
+    println!(
+        "Seed {}, {} pairs, {}",
+        game.seed(),
+        board.slots().count() / 2,
+        game.rule()
+    );
+    println!();
+    let width = board.cell_width();
+    println!(
+        "   {}",
+        (1..=board.cols()).map(|y| format!("{y:>width$}")).join(" ")
+    );
+    for (x, row) in board.iter().enumerate() {
+        let cards = row
+            .iter()
+            .map(|c| match c {
+                Some(c) => {
+                    let pad = width.saturating_sub(c.card().width());
+                    format!("{}{}", " ".repeat(pad), c.card())
+                }
+                None => " ".repeat(width),
+            })
+            .join(" ");
+        println!("{}  {cards}", utils::row_label(x));
+    }
+    println!();
+
+    let mut slots = board.slots().collect_vec();
+    while !slots.is_empty() {
+        let (pos, proxy) = slots.remove(0);
+        let Some(i) = slots
+            .iter()
+            .position(|(_, other)| game.rule().is_pair(proxy.card(), other.card()))
+        else {
+            continue;
+        };
+        let (other, _) = slots.remove(i);
+        println!(
+            "{:<4}{:<4}{}",
+            utils::pos_label(pos),
+            utils::pos_label(other),
+            proxy.card()
+        );
+    }
+}
+
+pub fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let plain = cli.plain || !io::stdin().is_terminal() || !io::stdout().is_terminal();
+    let command = match cli.command {
+        Some(command) => command,
+        None if plain => Command::Play(DealArgs::default()),
+        None => return Ok(start_ui(|_| {})?),
+    };
+    match command {
+        Command::Play(args) => {
+            let game = args.deal(&Settings::load().unwrap_or_default())?;
+            if plain {
+                line::run(game, io::stdin().lock(), io::stdout().lock())?;
+            } else {
+                start_ui(|app| app.enter_game(game))?;
+            }
+        }
+        Command::Solve(args) => solve(&args.deal(&Settings::load().unwrap_or_default())?),
+        Command::Replay { file } => {
+            let replay = save::load_replay(&file)
+                .map_err(|err| format!("can't read {}: {err}", file.display()))?;
+            if plain {
+                line::print_replay(&replay, io::stdout().lock())?;
+            } else {
+                start_ui(|app| app.watch_replay(replay))?;
+            }
+        }
+        Command::Stats => {
+            if plain {
+                let stats = Stats::load().map_err(|err| format!("can't read stats: {err}"))?;
+                line::print_stats(&stats, io::stdout().lock())?;
+            } else {
+                start_ui(|app| app.show_leaderboard())?;
+            }
+        }
+    }
+    Ok(())
+}
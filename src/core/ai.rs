@@ -0,0 +1,148 @@
+use super::{
+    game::{Game, Phase, Pos},
+    rules::PairRule,
+    tiles::{AnyTile, Tile},
+    utils,
+};
+use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+    Perfect,
+    /// Remembers cards with the given percentage as its retention.
+    Custom(u8),
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Perfect,
+    ];
+
+    /// Chance that a remembered card survives one more move in memory.
+    pub fn retention(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.0,
+            Difficulty::Medium => 0.7,
+            Difficulty::Hard => 0.9,
+            Difficulty::Perfect => 1.0,
+            Difficulty::Custom(memory) => (*memory).min(100) as f64 / 100.0,
+        }
+    }
+
+    /// Retention as a whole percentage.
+    pub fn memory(&self) -> u8 {
+        (self.retention() * 100.0).round() as u8
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difficulty::Custom(memory) => write!(f, "{memory}% memory"),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+/// A computer opponent that only sees what a human at the table would: cards
+/// as they are turned face up. Each remembered card is recalled with
+/// probability `retention ^ age`, where age counts the moves since it was
+/// last seen.
+#[derive(Clone, Debug)]
+pub struct Bot<T = AnyTile> {
+    retention: f64,
+    memory: HashMap<Pos, (T, u32)>,
+    clock: u32,
+    rng: ChaCha8Rng,
+}
+
+impl<T: Tile> Bot<T> {
+    pub fn new(difficulty: Difficulty, seed: u64) -> Self {
+        Self {
+            retention: difficulty.retention(),
+            memory: HashMap::new(),
+            clock: 0,
+            rng: utils::seeded_rng(seed),
+        }
+    }
+
+    /// Records every face-up card and drops cards that have left the board.
+    /// Call once after each move, whoever made it.
+    pub fn observe<R: PairRule<T>>(&mut self, game: &Game<R, T>) {
+        self.clock += 1;
+        let board = game.board();
+        self.memory.retain(|&(x, y), _| board.get(x, y).is_some());
+        for (pos, c) in board.slots().filter(|(_, c)| !c.is_flipped()) {
+            self.memory.insert(pos, (c.card().clone(), self.clock));
+        }
+    }
+
+    fn recall(&mut self) -> Vec<(Pos, T)> {
+        let (clock, retention) = (self.clock, self.retention);
+        let rng = &mut self.rng;
+        self.memory
+            .iter()
+            .sorted_by_key(|(&pos, _)| pos)
+            .filter(|(_, (_, seen))| rng.gen_bool(retention.powi((clock - seen) as i32)))
+            .map(|(&pos, (card, _))| (pos, card.clone()))
+            .collect_vec()
+    }
+
+    /// Picks the next card to reveal, or `None` when it isn't time to pick.
+    pub fn choose<R: PairRule<T>>(&mut self, game: &Game<R, T>) -> Option<Pos> {
+        let first = match game.phase() {
+            Phase::FirstPick => None,
+            Phase::SecondPick(first) => Some(first),
+            _ => return None,
+        };
+        let hidden = game
+            .board()
+            .slots()
+            .filter(|(_, c)| c.is_flipped())
+            .map(|(pos, _)| pos)
+            .collect_vec();
+        let known = self.recall();
+        let rule = game.rule();
+
+        let target = match first {
+            None => known
+                .iter()
+                .tuple_combinations()
+                .find(|((_, a), (_, b))| rule.is_pair(a, b))
+                .map(|((pos, _), _)| *pos),
+            Some(first) => {
+                let card = game.board().get(first.0, first.1)?.card();
+                known
+                    .iter()
+                    .find(|(pos, c)| *pos != first && rule.is_pair(card, c))
+                    .map(|(pos, _)| *pos)
+            }
+        };
+        if target.is_some() {
+            return target;
+        }
+
+        let unknown = hidden
+            .iter()
+            .filter(|pos| !known.iter().any(|(k, _)| k == *pos))
+            .copied()
+            .collect_vec();
+        let pool = if unknown.is_empty() {
+            &hidden
+        } else {
+            &unknown
+        };
+        pool.choose(&mut self.rng).copied()
+    }
+}
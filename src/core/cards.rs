@@ -1,9 +1,11 @@
+use super::rules::{PairRule, SameRankAndColor};
 use itertools::Itertools;
 use rand::{
     distributions::{Distribution, Standard},
     seq::SliceRandom,
     thread_rng, Rng,
 };
+use serde::{Deserialize, Serialize};
 use std::{fmt, ops::Deref};
 
 #[derive(Debug)]
@@ -12,7 +14,7 @@ pub enum CardConvertionError {
     IntoSuitError,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Rank {
     Ace,
     Two,
@@ -27,6 +29,7 @@ pub enum Rank {
     Jack,
     Queen,
     King,
+    Joker,
 }
 
 impl TryFrom<u8> for Rank {
@@ -68,13 +71,14 @@ impl fmt::Display for Rank {
             Rank::Jack => "J".to_owned(),
             Rank::Queen => "Q".to_owned(),
             Rank::King => "K".to_owned(),
+            Rank::Joker => "★".to_owned(),
             _ => (*self as u8 + 1).to_string(),
         };
         write!(f, "{s}")
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Suit {
     Clubs,
     Diamonds,
@@ -82,10 +86,10 @@ pub enum Suit {
     Spades,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-enum SuitColor {
-    RED,
-    BLACK,
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SuitColor {
+    Red,
+    Black,
 }
 
 impl TryFrom<u8> for Suit {
@@ -106,10 +110,10 @@ impl TryFrom<u8> for Suit {
 }
 
 impl Suit {
-    fn color(&self) -> SuitColor {
+    pub fn color(&self) -> SuitColor {
         match self {
-            &Suit::Clubs | &Suit::Spades => SuitColor::BLACK,
-            _ => SuitColor::RED,
+            &Suit::Clubs | &Suit::Spades => SuitColor::Black,
+            _ => SuitColor::Red,
         }
     }
 }
@@ -127,7 +131,7 @@ impl Distribution<Suit> for Standard {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Card {
     suit: Suit,
     rank: Rank,
@@ -138,9 +142,33 @@ impl Card {
         Self { suit, rank }
     }
 
-    fn color(&self) -> SuitColor {
+    pub fn joker(suit: Suit) -> Self {
+        Self::new(suit, Rank::Joker)
+    }
+
+    pub fn is_joker(&self) -> bool {
+        self.rank == Rank::Joker
+    }
+
+    pub fn suit(&self) -> Suit {
+        self.suit
+    }
+
+    pub fn rank(&self) -> Rank {
+        self.rank
+    }
+
+    pub fn color(&self) -> SuitColor {
         self.suit.color()
     }
+
+    /// Same rank and color; jokers pair with any other joker.
+    pub fn pairs_with(&self, other: &Card) -> bool {
+        if self.is_joker() || other.is_joker() {
+            return self.is_joker() && other.is_joker();
+        }
+        self.rank == other.rank && self.color() == other.color()
+    }
 }
 
 impl TryFrom<[u8; 2]> for Card {
@@ -156,81 +184,173 @@ impl TryFrom<[u8; 2]> for Card {
 
 impl fmt::Display for Card {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // let colorize_fn = |s: &str| match self.color() {
-        //     SuitColor::RED => format!(
-        //         "{}{:>3}{}",
-        //         color::Fg(color::Red),
-        //         s,
-        //         color::Fg(color::Reset)
-        //     ),
-        //     _ => format!(
-        //         "{}{}{}",
-        //         color::Fg(color::Black),
-        //         s,
-        //         color::Fg(color::Reset)
-        //     ),
-        // };
-
         let card_fmt = format!("{:>2}{}", self.rank.to_string(), self.suit);
-        // write!(f, "{}", colorize_fn(&card_fmt))
         write!(f, "{card_fmt}")
     }
 }
 
 impl Distribution<Card> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, _: &mut R) -> Card {
-        let suit: Suit = rand::random();
-        let rank: Rank = rand::random();
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Card {
+        let suit: Suit = rng.gen();
+        let rank: Rank = rng.gen();
         Card::new(suit, rank)
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-pub struct Deck([Card; 52]);
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeckKind {
+    #[default]
+    Standard,
+    Piquet,
+    FaceCards,
+}
 
-impl Deref for Deck {
-    type Target = [Card; 52];
+impl DeckKind {
+    pub const ALL: [DeckKind; 3] = [DeckKind::Standard, DeckKind::Piquet, DeckKind::FaceCards];
+
+    pub fn ranks(&self) -> Vec<Rank> {
+        let range = match self {
+            DeckKind::Standard => 0..13,
+            DeckKind::Piquet => 6..14,
+            DeckKind::FaceCards => 10..13,
+        };
+        // Piquet runs from seven up to the ace, which sits at index 0.
+        range.map(|r| Rank::try_from(r % 13).unwrap()).collect_vec()
+    }
+}
+
+impl fmt::Display for DeckKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            DeckKind::Standard => "Standard (52)",
+            DeckKind::Piquet => "Piquet (32)",
+            DeckKind::FaceCards => "Face cards (12)",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeckSpec {
+    pub kind: DeckKind,
+    pub decks: u8,
+    pub jokers: u8,
+}
+
+impl Default for DeckSpec {
+    fn default() -> Self {
+        Self {
+            kind: DeckKind::Standard,
+            decks: 1,
+            jokers: 0,
+        }
+    }
+}
+
+impl DeckSpec {
+    /// Stacks `decks` copies of the kind, each with `jokers` jokers of
+    /// alternating color. Jokers pair with each other whatever their color.
+    pub fn cards(&self) -> Vec<Card> {
+        let ranks = self.kind.ranks();
+        let single = (0..4)
+            .cartesian_product(ranks)
+            .map(|(s, r)| Card::new(s.try_into().unwrap(), r))
+            .chain((0..self.jokers).map(|i| {
+                Card::joker(if i % 2 == 0 {
+                    Suit::Hearts
+                } else {
+                    Suit::Spades
+                })
+            }))
+            .collect_vec();
+        (0..self.decks).flat_map(|_| single.clone()).collect_vec()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Deck<T = Card>(Vec<T>);
+
+impl<T> Deref for Deck<T> {
+    type Target = [T];
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-#[allow(dead_code)] // TODO: 
+impl<T> Deck<T> {
+    pub fn from_cards(cards: Vec<T>) -> Self {
+        Self(cards)
+    }
+
+    /// A paired deck laid out from its pairs, in order.
+    pub fn from_pairs(pairs: impl IntoIterator<Item = [T; 2]>) -> Self {
+        Self(pairs.into_iter().flatten().collect())
+    }
+
+    /// The same deck with every card turned into another kind of tile.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Deck<U> {
+        Deck(self.0.into_iter().map(f).collect())
+    }
+
+    /// Number of leading pairs in the deck that hold up under `rule`.
+    pub fn pair_count<R: PairRule<T> + ?Sized>(&self, rule: &R) -> usize {
+        self.chunks_exact(2)
+            .take_while(|c| rule.is_pair(&c[0], &c[1]))
+            .count()
+    }
+}
+
 impl Deck {
-    fn all_cards() -> [Card; 52] {
-        (0..4)
-            .cartesian_product(0..13)
-            .map(|(r, s)| Card::try_from([r, s]).unwrap())
-            .collect_vec()
-            .try_into()
-            .unwrap()
+    fn all_cards() -> Vec<Card> {
+        DeckSpec::default().cards()
     }
 
-    fn all_cards_paired() -> [Card; 52] {
-        let mut cards = Deck::all_cards();
-        cards.sort_by_key(|c| c.color());
-        cards.sort_by_key(|c| c.rank);
-        cards
+    /// Greedily groups cards into pairs under `rule`, returning the pairs
+    /// alongside the cards left without a partner.
+    fn pair_up<R: PairRule + ?Sized>(cards: Vec<Card>, rule: &R) -> (Vec<[Card; 2]>, Vec<Card>) {
+        let mut rest = cards;
+        let mut pairs = vec![];
+        let mut leftover = vec![];
+        while !rest.is_empty() {
+            let card = rest.remove(0);
+            match rest.iter().position(|c| rule.is_pair(&card, c)) {
+                Some(i) => pairs.push([card, rest.remove(i)]),
+                None => leftover.push(card),
+            }
+        }
+        (pairs, leftover)
+    }
+
+    fn cards_paired_by<R: PairRule + ?Sized>(cards: Vec<Card>, rule: &R) -> Vec<Card> {
+        let (mut pairs, leftover) = Deck::pair_up(cards, rule);
+        pairs.sort_by_key(|[a, _]| a.rank);
+        pairs.into_iter().flatten().chain(leftover).collect_vec()
+    }
+
+    fn cards_paired_shuffled_by<P: PairRule + ?Sized, R: Rng + ?Sized>(
+        cards: Vec<Card>,
+        rule: &P,
+        rng: &mut R,
+    ) -> Vec<Card> {
+        let (mut pairs, leftover) = Deck::pair_up(cards, rule);
+        pairs.shuffle(rng);
+        pairs.into_iter().flatten().chain(leftover).collect_vec()
     }
 
-    fn all_cards_shuffled() -> [Card; 52] {
+    fn all_cards_paired() -> Vec<Card> {
+        Deck::cards_paired_by(Deck::all_cards(), &SameRankAndColor)
+    }
+
+    fn all_cards_shuffled<R: Rng + ?Sized>(rng: &mut R) -> Vec<Card> {
         let mut cards = Deck::all_cards();
-        cards.shuffle(&mut thread_rng());
+        cards.shuffle(rng);
         cards
     }
 
-    fn all_cards_paired_shuffled() -> [Card; 52] {
-        let cards = Deck::all_cards_paired();
-        let mut chunked = cards.chunks_exact(2).collect_vec();
-        chunked.shuffle(&mut thread_rng());
-        chunked
-            .into_iter()
-            .flatten()
-            .cloned()
-            .collect_vec()
-            .try_into()
-            .unwrap()
+    fn all_cards_paired_shuffled<R: Rng + ?Sized>(rng: &mut R) -> Vec<Card> {
+        Deck::cards_paired_shuffled_by(Deck::all_cards(), &SameRankAndColor, rng)
     }
 
     pub fn new() -> Self {
@@ -242,10 +362,137 @@ impl Deck {
     }
 
     pub fn shuffled() -> Self {
-        Self(Self::all_cards_shuffled())
+        Self::shuffled_with(&mut thread_rng())
     }
 
     pub fn paired_shuffled() -> Self {
-        Self(Self::all_cards_paired_shuffled())
+        Self::paired_shuffled_with(&mut thread_rng())
+    }
+
+    pub fn shuffled_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self(Self::all_cards_shuffled(rng))
+    }
+
+    pub fn paired_shuffled_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self(Self::all_cards_paired_shuffled(rng))
+    }
+
+    pub fn paired_by<R: PairRule + ?Sized>(rule: &R) -> Self {
+        Self::new().into_paired_by(rule)
+    }
+
+    pub fn paired_shuffled_by<P: PairRule + ?Sized, R: Rng + ?Sized>(
+        rule: &P,
+        rng: &mut R,
+    ) -> Self {
+        Self::new().into_paired_shuffled_by(rule, rng)
+    }
+
+    pub fn from_spec(spec: &DeckSpec) -> Self {
+        Self(spec.cards())
+    }
+
+    pub fn into_paired_by<R: PairRule + ?Sized>(self, rule: &R) -> Self {
+        Self(Self::cards_paired_by(self.0, rule))
+    }
+
+    pub fn into_paired_shuffled_by<P: PairRule + ?Sized, R: Rng + ?Sized>(
+        self,
+        rule: &P,
+        rng: &mut R,
+    ) -> Self {
+        Self(Self::cards_paired_shuffled_by(self.0, rule, rng))
+    }
+}
+
+impl Default for Deck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::rules::PairRuleKind;
+
+    fn spec(kind: DeckKind) -> DeckSpec {
+        DeckSpec {
+            kind,
+            ..DeckSpec::default()
+        }
+    }
+
+    fn pair_count(spec: &DeckSpec, rule: PairRuleKind) -> usize {
+        Deck::from_spec(spec)
+            .into_paired_by(&rule)
+            .pair_count(&rule)
+    }
+
+    #[test]
+    fn deck_kinds_hold_their_cards() {
+        assert_eq!(spec(DeckKind::Standard).cards().len(), 52);
+        assert_eq!(spec(DeckKind::Piquet).cards().len(), 32);
+        assert_eq!(spec(DeckKind::FaceCards).cards().len(), 12);
+        let piquet = spec(DeckKind::Piquet).cards();
+        assert!(piquet.iter().any(|c| c.rank() == Rank::Ace));
+        assert!(piquet.iter().all(|c| c.rank() != Rank::Six));
+    }
+
+    #[test]
+    fn pair_count_by_rule_and_deck_kind() {
+        use DeckKind::*;
+        use PairRuleKind::*;
+        // The adjacent-rank rule leaves kings over, and in piquet also aces,
+        // whose partner would be the two.
+        let expected = [
+            (Standard, RankAndColor, 26),
+            (Standard, RankOnly, 26),
+            (Standard, SuitAndAdjacentRank, 24),
+            (Piquet, RankAndColor, 16),
+            (Piquet, RankOnly, 16),
+            (Piquet, SuitAndAdjacentRank, 12),
+            (FaceCards, RankAndColor, 6),
+            (FaceCards, RankOnly, 6),
+            (FaceCards, SuitAndAdjacentRank, 4),
+        ];
+        for (kind, rule, pairs) in expected {
+            assert_eq!(pair_count(&spec(kind), rule), pairs, "{kind:?} {rule:?}");
+        }
+    }
+
+    #[test]
+    fn stacked_decks_double_the_pairs() {
+        for kind in DeckKind::ALL {
+            for rule in PairRuleKind::ALL {
+                let double = DeckSpec {
+                    decks: 2,
+                    ..spec(kind)
+                };
+                assert_eq!(double.cards().len(), 2 * spec(kind).cards().len());
+                assert_eq!(
+                    pair_count(&double, rule),
+                    2 * pair_count(&spec(kind), rule),
+                    "{kind:?} {rule:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pairing_is_greedy() {
+        let ace = |suit| Card::new(suit, Rank::Ace);
+        let cards = vec![ace(Suit::Hearts), ace(Suit::Spades), ace(Suit::Diamonds)];
+        let (pairs, leftover) = Deck::pair_up(cards, &PairRuleKind::RankOnly);
+        assert_eq!(pairs, [[ace(Suit::Hearts), ace(Suit::Spades)]]);
+        assert_eq!(leftover, [ace(Suit::Diamonds)]);
+
+        let deck = Deck::from_cards(vec![ace(Suit::Clubs), ace(Suit::Hearts), ace(Suit::Spades)])
+            .into_paired_by(&PairRuleKind::RankAndColor);
+        assert_eq!(
+            &*deck,
+            [ace(Suit::Clubs), ace(Suit::Spades), ace(Suit::Hearts)]
+        );
+        assert_eq!(deck.pair_count(&PairRuleKind::RankAndColor), 1);
     }
 }
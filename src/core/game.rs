@@ -0,0 +1,662 @@
+use super::{
+    cards::Deck,
+    rules::{PairRule, PairRuleKind},
+    tiles::{AnyTile, Tile},
+    utils, Board, InvalidBoardSizeError, Player,
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub type Pos = (u8, u8);
+
+#[derive(Debug)]
+pub enum GameError {
+    InvalidBoardSize,
+    NoPlayers,
+    NotEnoughPairs,
+    EmptySlot,
+    AlreadyRevealed,
+    TurnUnresolved,
+    NoMismatch,
+    GameOver,
+    UndoUnavailable,
+    NothingToUndo,
+    NothingToRedo,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            GameError::InvalidBoardSize => "Invalid board size.",
+            GameError::NoPlayers => "At least one player is required.",
+            GameError::NotEnoughPairs => "The deck has too few pairs for this board size.",
+            GameError::EmptySlot => "There is no card there.",
+            GameError::AlreadyRevealed => "That card is already face up.",
+            GameError::TurnUnresolved => "The current turn has not been resolved yet.",
+            GameError::NoMismatch => "There is no mismatch to resolve.",
+            GameError::GameOver => "The game is over.",
+            GameError::UndoUnavailable => "Undo is only available in single-player games.",
+            GameError::NothingToUndo => "Nothing to undo.",
+            GameError::NothingToRedo => "Nothing to redo.",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl std::error::Error for GameError {}
+
+impl From<InvalidBoardSizeError> for GameError {
+    fn from(_: InvalidBoardSizeError) -> Self {
+        GameError::InvalidBoardSize
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    FirstPick,
+    SecondPick(Pos),
+    Mismatch(Pos, Pos),
+    Over,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reveal {
+    First(Pos),
+    Match(Pos, Pos),
+    Mismatch(Pos, Pos),
+}
+
+/// The parts of a game that moves change.
+#[derive(Clone, Debug)]
+struct Snapshot<T> {
+    board: Board<T>,
+    players: Vec<Player<T>>,
+    turn: usize,
+    phase: Phase,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(deserialize = "R: Deserialize<'de>, T: Deserialize<'de>"))]
+pub struct Game<R: PairRule<T> = PairRuleKind, T: Tile = AnyTile> {
+    board: Board<T>,
+    players: Vec<Player<T>>,
+    rule: R,
+    seed: u64,
+    #[serde(default)]
+    size: u8,
+    turn: usize,
+    phase: Phase,
+    #[serde(skip)]
+    undo: Vec<Snapshot<T>>,
+    #[serde(skip)]
+    redo: Vec<Snapshot<T>>,
+}
+
+impl<R: PairRule<T>, T: Tile> Game<R, T> {
+    /// Deals `size` pairs from a paired `deck`; the same seed always deals the
+    /// same board.
+    pub fn new(
+        deck: &Deck<T>,
+        size: u8,
+        players: Vec<Player<T>>,
+        rule: R,
+        seed: u64,
+    ) -> Result<Self, GameError> {
+        if players.is_empty() {
+            return Err(GameError::NoPlayers);
+        }
+        let pairs = deck.pair_count(&rule);
+        if pairs < size as usize {
+            return Err(GameError::NotEnoughPairs);
+        }
+        let mut rng = utils::seeded_rng(seed);
+        Ok(Self {
+            board: Board::sampled(deck, pairs, size, &mut rng)?,
+            players,
+            rule,
+            seed,
+            size,
+            turn: 0,
+            phase: Phase::FirstPick,
+            undo: vec![],
+            redo: vec![],
+        })
+    }
+
+    pub fn board(&self) -> &Board<T> {
+        &self.board
+    }
+
+    pub fn players(&self) -> &[Player<T>] {
+        &self.players
+    }
+
+    pub fn rule(&self) -> &R {
+        &self.rule
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Pairs dealt, however many have been taken since.
+    pub fn size(&self) -> u8 {
+        self.size
+    }
+
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn current_player(&self) -> &Player<T> {
+        &self.players[self.turn]
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.phase == Phase::Over
+    }
+
+    /// Players holding the most pairs; more than one means a tie.
+    pub fn winners(&self) -> Vec<&Player<T>> {
+        let best = self.players.iter().map(Player::pairs).max().unwrap_or(0);
+        self.players
+            .iter()
+            .filter(|p| p.pairs() == best)
+            .collect_vec()
+    }
+
+    /// A copy of the game that reveals nothing a player at the table can't see.
+    /// The seed is left out too, since the board could be dealt again from it.
+    pub fn masked(&self) -> Self
+    where
+        R: Clone,
+    {
+        Self {
+            board: self.board.masked(),
+            seed: 0,
+            undo: vec![],
+            redo: vec![],
+            ..self.clone()
+        }
+    }
+
+    /// Practice games have a single player, and only they can take moves back.
+    pub fn is_practice(&self) -> bool {
+        self.players.len() == 1
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.is_practice() && !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.is_practice() && !self.redo.is_empty()
+    }
+
+    fn snapshot(&self) -> Snapshot<T> {
+        Snapshot {
+            board: self.board.clone(),
+            players: self.players.clone(),
+            turn: self.turn,
+            phase: self.phase,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot<T>) -> Snapshot<T> {
+        let current = self.snapshot();
+        self.board = snapshot.board;
+        self.players = snapshot.players;
+        self.turn = snapshot.turn;
+        self.phase = snapshot.phase;
+        current
+    }
+
+    /// Remembers the state from before a successful move, in practice games.
+    fn record_move(&mut self, before: Option<Snapshot<T>>) {
+        if let Some(before) = before {
+            self.undo.push(before);
+            self.redo.clear();
+        }
+    }
+
+    /// Takes back the last reveal or end of turn.
+    pub fn undo(&mut self) -> Result<(), GameError> {
+        if !self.is_practice() {
+            return Err(GameError::UndoUnavailable);
+        }
+        let snapshot = self.undo.pop().ok_or(GameError::NothingToUndo)?;
+        let current = self.restore(snapshot);
+        self.redo.push(current);
+        Ok(())
+    }
+
+    pub fn redo(&mut self) -> Result<(), GameError> {
+        if !self.is_practice() {
+            return Err(GameError::UndoUnavailable);
+        }
+        let snapshot = self.redo.pop().ok_or(GameError::NothingToRedo)?;
+        let current = self.restore(snapshot);
+        self.undo.push(current);
+        Ok(())
+    }
+
+    pub fn reveal(&mut self, pos: Pos) -> Result<Reveal, GameError> {
+        let before = self.is_practice().then(|| self.snapshot());
+        let res = self.apply_reveal(pos)?;
+        self.record_move(before);
+        Ok(res)
+    }
+
+    fn apply_reveal(&mut self, pos: Pos) -> Result<Reveal, GameError> {
+        let first = match self.phase {
+            Phase::Over => return Err(GameError::GameOver),
+            Phase::Mismatch(..) => return Err(GameError::TurnUnresolved),
+            Phase::FirstPick => None,
+            Phase::SecondPick(first) => Some(first),
+        };
+        match self.board.get(pos.0, pos.1) {
+            None => return Err(GameError::EmptySlot),
+            Some(c) if !c.is_flipped() => return Err(GameError::AlreadyRevealed),
+            Some(_) => self.board.flip(pos.0, pos.1),
+        }
+
+        let Some(first) = first else {
+            self.phase = Phase::SecondPick(pos);
+            return Ok(Reveal::First(pos));
+        };
+
+        let a = self.board.get(first.0, first.1).unwrap().card();
+        let b = self.board.get(pos.0, pos.1).unwrap().card();
+        if !self.rule.is_pair(a, b) {
+            self.players[self.turn].misses += 1;
+            self.phase = Phase::Mismatch(first, pos);
+            return Ok(Reveal::Mismatch(first, pos));
+        }
+
+        let a = self.board.remove(first.0, first.1).unwrap().card;
+        let b = self.board.remove(pos.0, pos.1).unwrap().card;
+        self.players[self.turn].paired_cards.extend([a, b]);
+        self.phase = if self.board.is_cleared() {
+            Phase::Over
+        } else {
+            Phase::FirstPick
+        };
+        Ok(Reveal::Match(first, pos))
+    }
+
+    /// Gives up the current turn, e.g. when its time runs out: any face-up
+    /// cards go back face down and the turn counts as a miss.
+    pub fn forfeit_turn(&mut self) -> Result<(), GameError> {
+        let before = self.is_practice().then(|| self.snapshot());
+        match self.phase {
+            Phase::Over => return Err(GameError::GameOver),
+            Phase::FirstPick => self.players[self.turn].misses += 1,
+            Phase::SecondPick(a) => {
+                self.board.flip(a.0, a.1);
+                self.players[self.turn].misses += 1;
+            }
+            Phase::Mismatch(a, b) => {
+                self.board.flip(a.0, a.1);
+                self.board.flip(b.0, b.1);
+            }
+        }
+        self.turn = (self.turn + 1) % self.players.len();
+        self.phase = Phase::FirstPick;
+        self.record_move(before);
+        Ok(())
+    }
+
+    /// Ends the game where it stands; whoever holds the most pairs wins.
+    pub fn time_up(&mut self) {
+        if let Phase::SecondPick(a) = self.phase {
+            self.board.flip(a.0, a.1);
+        }
+        if let Phase::Mismatch(a, b) = self.phase {
+            self.board.flip(a.0, a.1);
+            self.board.flip(b.0, b.1);
+        }
+        self.phase = Phase::Over;
+    }
+
+    /// Flips a mismatched pair back face down and passes the turn on.
+    pub fn end_turn(&mut self) -> Result<(), GameError> {
+        let Phase::Mismatch(a, b) = self.phase else {
+            return Err(GameError::NoMismatch);
+        };
+        let before = self.is_practice().then(|| self.snapshot());
+        self.board.flip(a.0, a.1);
+        self.board.flip(b.0, b.1);
+        self.turn = (self.turn + 1) % self.players.len();
+        self.phase = Phase::FirstPick;
+        self.record_move(before);
+        Ok(())
+    }
+}
+
+/// Games and lookups shared by the tests around the crate.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::*;
+    use crate::core::{cards::Deck, rules::PairRule, tiles};
+
+    /// A game over one pair of each symbol, dealt with seed 1.
+    pub(crate) fn game(names: &[&str], symbols: &[&str]) -> Game {
+        let symbols = symbols.iter().map(|s| s.to_string()).collect_vec();
+        let deck = tiles::symbol_deck(&symbols);
+        let players = names.iter().map(|n| Player::new(n.to_string())).collect();
+        Game::new(
+            &deck,
+            symbols.len() as u8,
+            players,
+            PairRuleKind::default(),
+            1,
+        )
+        .unwrap()
+    }
+
+    /// `pairs` pairs of playing cards between Ann and Bob.
+    pub(crate) fn card_game(pairs: u8) -> Game {
+        let deck = Deck::paired().map(AnyTile::Card);
+        let players = vec![Player::new("Ann".into()), Player::new("Bob".into())];
+        Game::new(&deck, pairs, players, PairRuleKind::default(), 42).unwrap()
+    }
+
+    /// Where the two cards showing `symbol` lie.
+    pub(crate) fn find(game: &Game, symbol: &str) -> [Pos; 2] {
+        let slots = game
+            .board()
+            .slots()
+            .filter(|(_, c)| c.card().to_string() == symbol)
+            .map(|(pos, _)| pos)
+            .collect_vec();
+        slots.try_into().unwrap()
+    }
+
+    /// The partner of the card at `pos` on the board.
+    pub(crate) fn partner(game: &Game, pos: Pos) -> Pos {
+        let card = game.board().get(pos.0, pos.1).unwrap().card();
+        let (other, _) = game
+            .board()
+            .slots()
+            .find(|&(p, c)| p != pos && game.rule().is_pair(card, c.card()))
+            .unwrap();
+        other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fixtures::*, *};
+    use crate::core::tiles;
+
+    fn is_face_down(game: &Game, pos: Pos) -> bool {
+        game.board().get(pos.0, pos.1).unwrap().is_flipped()
+    }
+
+    #[test]
+    fn matching_pair_is_taken_and_the_turn_stays() {
+        let mut game = game(&["Ann", "Bob"], &["A", "B"]);
+        let [a, b] = find(&game, "A");
+
+        assert_eq!(game.reveal(a).unwrap(), Reveal::First(a));
+        assert_eq!(game.phase(), Phase::SecondPick(a));
+        assert!(!is_face_down(&game, a));
+
+        assert_eq!(game.reveal(b).unwrap(), Reveal::Match(a, b));
+        assert!(game.board().get(a.0, a.1).is_none());
+        assert!(game.board().get(b.0, b.1).is_none());
+        assert_eq!(game.players()[0].pairs(), 1);
+        assert_eq!(game.turn(), 0);
+        assert_eq!(game.phase(), Phase::FirstPick);
+    }
+
+    #[test]
+    fn mismatch_waits_for_the_end_of_the_turn() {
+        let mut game = game(&["Ann", "Bob"], &["A", "B"]);
+        let [a, _] = find(&game, "A");
+        let [b, _] = find(&game, "B");
+
+        game.reveal(a).unwrap();
+        assert_eq!(game.reveal(b).unwrap(), Reveal::Mismatch(a, b));
+        assert_eq!(game.phase(), Phase::Mismatch(a, b));
+        assert_eq!(game.players()[0].misses, 1);
+        assert!(matches!(
+            game.reveal(find(&game, "A")[1]),
+            Err(GameError::TurnUnresolved)
+        ));
+
+        game.end_turn().unwrap();
+        assert!(is_face_down(&game, a) && is_face_down(&game, b));
+        assert_eq!(game.turn(), 1);
+        assert_eq!(game.current_player().name, "Bob");
+        assert_eq!(game.phase(), Phase::FirstPick);
+    }
+
+    #[test]
+    fn invalid_moves_are_refused() {
+        let mut game = game(&["Ann"], &["A", "B"]);
+        let [a, b] = find(&game, "A");
+
+        assert!(matches!(game.end_turn(), Err(GameError::NoMismatch)));
+        game.reveal(a).unwrap();
+        assert!(matches!(game.reveal(a), Err(GameError::AlreadyRevealed)));
+        game.reveal(b).unwrap();
+        assert!(matches!(game.reveal(a), Err(GameError::EmptySlot)));
+        assert!(matches!(game.reveal((99, 99)), Err(GameError::EmptySlot)));
+    }
+
+    #[test]
+    fn game_is_over_once_the_board_is_cleared() {
+        let mut game = game(&["Ann", "Bob"], &["A", "B", "C"]);
+        let [a0, a1] = find(&game, "A");
+        let [b0, b1] = find(&game, "B");
+        let [c0, c1] = find(&game, "C");
+
+        game.reveal(a0).unwrap();
+        game.reveal(a1).unwrap();
+        game.reveal(b0).unwrap();
+        game.reveal(c0).unwrap();
+        game.end_turn().unwrap();
+        for pos in [b0, b1, c0, c1] {
+            game.reveal(pos).unwrap();
+        }
+
+        assert!(game.is_over());
+        assert!(game.board().is_cleared());
+        assert!(matches!(game.reveal(a0), Err(GameError::GameOver)));
+        let winners = game.winners();
+        assert_eq!(winners.len(), 1);
+        assert_eq!(winners[0].name, "Bob");
+        assert_eq!(winners[0].pairs(), 2);
+    }
+
+    #[test]
+    fn even_scores_tie() {
+        let mut game = game(&["Ann", "Bob"], &["A", "B", "C", "D"]);
+        let [a0, a1] = find(&game, "A");
+        let [b0, b1] = find(&game, "B");
+        let [c0, c1] = find(&game, "C");
+        let [d0, d1] = find(&game, "D");
+
+        for pos in [a0, a1, b0, b1, c0, d0] {
+            game.reveal(pos).unwrap();
+        }
+        game.end_turn().unwrap();
+        for pos in [c0, c1, d0, d1] {
+            game.reveal(pos).unwrap();
+        }
+
+        assert!(game.is_over());
+        let winners = game.winners().iter().map(|p| p.name.as_str()).collect_vec();
+        assert_eq!(winners, ["Ann", "Bob"]);
+    }
+
+    #[test]
+    fn game_needs_players_and_enough_pairs() {
+        let deck = tiles::symbol_deck(&["A".to_owned()]);
+        let rule = PairRuleKind::default();
+        let players = || vec![Player::new("Ann".to_owned())];
+        assert!(matches!(
+            Game::new(&deck, 1, vec![], rule, 1),
+            Err(GameError::NoPlayers)
+        ));
+        assert!(matches!(
+            Game::new(&deck, 2, players(), rule, 1),
+            Err(GameError::NotEnoughPairs)
+        ));
+        assert!(matches!(
+            Game::new(&deck, 0, players(), rule, 1),
+            Err(GameError::InvalidBoardSize)
+        ));
+    }
+
+    #[test]
+    fn practice_moves_can_be_undone_and_redone() {
+        let mut game = game(&["Ann"], &["A", "B"]);
+        let [a0, a1] = find(&game, "A");
+        assert!(matches!(game.undo(), Err(GameError::NothingToUndo)));
+
+        game.reveal(a0).unwrap();
+        game.reveal(a1).unwrap();
+        assert_eq!(game.players()[0].pairs(), 1);
+
+        game.undo().unwrap();
+        assert_eq!(game.players()[0].pairs(), 0);
+        assert_eq!(game.phase(), Phase::SecondPick(a0));
+        assert!(!is_face_down(&game, a0) && is_face_down(&game, a1));
+
+        game.undo().unwrap();
+        assert_eq!(game.phase(), Phase::FirstPick);
+        assert!(is_face_down(&game, a0));
+        assert!(!game.can_undo() && game.can_redo());
+
+        game.redo().unwrap();
+        game.redo().unwrap();
+        assert_eq!(game.players()[0].pairs(), 1);
+        assert!(matches!(game.redo(), Err(GameError::NothingToRedo)));
+    }
+
+    #[test]
+    fn a_new_move_drops_the_redo_history() {
+        let mut game = game(&["Ann"], &["A", "B"]);
+        let [a0, _] = find(&game, "A");
+        let [b0, _] = find(&game, "B");
+
+        game.reveal(a0).unwrap();
+        game.undo().unwrap();
+        game.reveal(b0).unwrap();
+        assert!(!game.can_redo());
+        assert!(matches!(game.redo(), Err(GameError::NothingToRedo)));
+    }
+
+    #[test]
+    fn mismatches_can_be_undone_after_the_turn_ends() {
+        let mut game = game(&["Ann"], &["A", "B"]);
+        let [a0, _] = find(&game, "A");
+        let [b0, _] = find(&game, "B");
+
+        game.reveal(a0).unwrap();
+        game.reveal(b0).unwrap();
+        game.end_turn().unwrap();
+        game.undo().unwrap();
+        assert_eq!(game.phase(), Phase::Mismatch(a0, b0));
+        game.undo().unwrap();
+        assert_eq!(game.players()[0].misses, 0);
+    }
+
+    #[test]
+    fn undo_is_only_for_practice_games() {
+        let mut game = game(&["Ann", "Bob"], &["A", "B"]);
+        let [a0, _] = find(&game, "A");
+
+        game.reveal(a0).unwrap();
+        assert!(!game.can_undo());
+        assert!(matches!(game.undo(), Err(GameError::UndoUnavailable)));
+        assert!(matches!(game.redo(), Err(GameError::UndoUnavailable)));
+    }
+
+    #[test]
+    fn forfeit_turns_the_card_back_and_passes_the_turn() {
+        let mut game = game(&["Ann", "Bob"], &["A", "B"]);
+        let [a0, _] = find(&game, "A");
+
+        game.reveal(a0).unwrap();
+        game.forfeit_turn().unwrap();
+        assert!(is_face_down(&game, a0));
+        assert_eq!(game.players()[0].misses, 1);
+        assert_eq!(game.turn(), 1);
+        assert_eq!(game.phase(), Phase::FirstPick);
+
+        game.forfeit_turn().unwrap();
+        assert_eq!(game.players()[1].misses, 1);
+        assert_eq!(game.turn(), 0);
+    }
+
+    #[test]
+    fn forfeit_during_a_mismatch_counts_one_miss() {
+        let mut game = game(&["Ann", "Bob"], &["A", "B"]);
+        let [a0, _] = find(&game, "A");
+        let [b0, _] = find(&game, "B");
+
+        game.reveal(a0).unwrap();
+        game.reveal(b0).unwrap();
+        game.forfeit_turn().unwrap();
+        assert!(is_face_down(&game, a0) && is_face_down(&game, b0));
+        assert_eq!(game.players()[0].misses, 1);
+        assert_eq!(game.turn(), 1);
+    }
+
+    #[test]
+    fn time_up_ends_the_game_where_it_stands() {
+        let mut game = game(&["Ann", "Bob"], &["A", "B", "C"]);
+        let [a0, a1] = find(&game, "A");
+        let [b0, _] = find(&game, "B");
+
+        game.reveal(a0).unwrap();
+        game.reveal(a1).unwrap();
+        game.reveal(b0).unwrap();
+        game.time_up();
+
+        assert!(game.is_over());
+        assert!(!game.board().is_cleared());
+        assert!(is_face_down(&game, b0));
+        assert!(matches!(game.reveal(b0), Err(GameError::GameOver)));
+        assert!(matches!(game.forfeit_turn(), Err(GameError::GameOver)));
+        let winners = game.winners();
+        assert_eq!(winners.len(), 1);
+        assert_eq!(winners[0].name, "Ann");
+    }
+
+    #[test]
+    fn same_seed_deals_the_same_board() {
+        let deck = Deck::paired().map(AnyTile::Card);
+        let deal = |seed| {
+            let players = vec![Player::new("Ann".to_owned())];
+            Game::new(&deck, 8, players, PairRuleKind::default(), seed).unwrap()
+        };
+        assert_eq!(deal(42).board(), deal(42).board());
+        assert_ne!(deal(42).board(), deal(43).board());
+        assert_eq!(deal(42).seed(), 42);
+
+        // Pinned so a change to the RNG or the layout shows up here.
+        let cards = deal(42)
+            .board()
+            .iter()
+            .map(|row| row.iter().flatten().map(|c| c.card().to_string()).join(" "))
+            .collect_vec();
+        assert_eq!(
+            cards,
+            [
+                " 3♧  3♤  9♧  K♡",
+                " 9♤  8♤ 10♡  9♡",
+                " 9♢  8♧  5♢  5♡",
+                " 4♡  4♢  K♢ 10♢",
+            ]
+        );
+    }
+}
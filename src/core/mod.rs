@@ -1,63 +1,102 @@
+pub mod ai;
 pub mod cards;
 pub mod extras;
+pub mod game;
+pub mod replay;
+pub mod rules;
+pub mod tiles;
 pub mod utils;
 
 use itertools::Itertools;
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{
+    seq::{index, SliceRandom},
+    Rng,
+};
+use serde::{Deserialize, Serialize};
 use std::{fmt, ops::Deref};
+use tiles::{AnyTile, Tile};
 
-#[derive(Clone)]
-pub struct CardProxy<'a> {
-    card: &'a cards::Card,
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardProxy<T = AnyTile> {
+    card: T,
     flipped: bool,
 }
 
-impl<'a> CardProxy<'a> {
-    fn new(card: &'a cards::Card) -> Self {
+impl<T> CardProxy<T> {
+    fn new(card: T) -> Self {
         Self {
             card,
             flipped: true,
         }
     }
+
+    pub fn card(&self) -> &T {
+        &self.card
+    }
+
+    pub fn is_flipped(&self) -> bool {
+        self.flipped
+    }
 }
 
 #[derive(Debug)]
 pub struct InvalidBoardSizeError;
 
-pub struct Board<'a>(Vec<Vec<Option<CardProxy<'a>>>>);
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Board<T = AnyTile>(Vec<Vec<Option<CardProxy<T>>>>);
+
+impl<T: Tile> Board<T> {
+    pub fn new<R: Rng + ?Sized>(
+        deck: &cards::Deck<T>,
+        size: u8,
+        rng: &mut R,
+    ) -> Result<Self, InvalidBoardSizeError> {
+        Board::sampled(deck, size as usize, size, rng)
+    }
 
-impl<'a> Board<'a> {
-    pub fn new(deck: &'a cards::Deck, size: u8) -> Result<Board<'a>, InvalidBoardSizeError> {
-        if size > 26 || size <= 0 {
+    /// Deals `size` pairs picked at random from the first `pairs` pairs of a
+    /// paired deck, laid out as [`utils::deal_cols`] says.
+    pub fn sampled<R: Rng + ?Sized>(
+        deck: &cards::Deck<T>,
+        pairs: usize,
+        size: u8,
+        rng: &mut R,
+    ) -> Result<Self, InvalidBoardSizeError> {
+        if size == 0 || size as usize > pairs || pairs * 2 > deck.len() {
             return Err(InvalidBoardSizeError);
         }
 
-        let [x, _] = utils::squarest_rect_with_even_area(size as u32);
+        let cols = utils::deal_cols(size as usize * 2);
 
-        let mut taken_cards = deck
-            .iter()
-            .take(size as usize * 2)
-            .map(|c| Some(CardProxy::new(c)))
+        let mut taken_cards = index::sample(rng, pairs, size as usize)
+            .into_iter()
+            .flat_map(|i| &deck[i * 2..i * 2 + 2])
+            .map(|c| Some(CardProxy::new(c.clone())))
             .collect_vec();
-        taken_cards.shuffle(&mut thread_rng());
+        taken_cards.shuffle(rng);
         let vec = taken_cards
-            .chunks(x as usize)
-            .map(|c| c.to_owned())
+            .chunks(cols)
+            .map(|c| {
+                let mut row = c.to_owned();
+                row.resize(cols, None);
+                row
+            })
             .collect_vec();
         Ok(Board(vec))
     }
 }
 
-impl<'a> Deref for Board<'a> {
-    type Target = Vec<Vec<Option<CardProxy<'a>>>>;
+impl<T> Deref for Board<T> {
+    type Target = Vec<Vec<Option<CardProxy<T>>>>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-impl fmt::Display for Board<'_> {
+impl<T: Tile> fmt::Display for Board<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.cell_width();
         let formatted = self
             .iter()
             .map(|c| {
@@ -65,17 +104,18 @@ impl fmt::Display for Board<'_> {
                     .map(|c| match c {
                         Some(c) => {
                             if c.flipped {
-                                "---".to_owned()
+                                "-".repeat(width)
                                 // format!(
                                 //     "{}---{}",
                                 //     color::Fg(color::LightBlue),
                                 //     color::Fg(color::Reset)
                                 // )
                             } else {
-                                c.card.to_string()
+                                let pad = width.saturating_sub(c.card.width());
+                                format!("{}{}", " ".repeat(pad), c.card)
                             }
                         }
-                        _ => "   ".to_owned(),
+                        _ => " ".repeat(width),
                     })
                     .join(" ")
             })
@@ -84,23 +124,102 @@ impl fmt::Display for Board<'_> {
     }
 }
 
-impl Board<'_> {
+impl<T: Tile> Board<T> {
     pub fn flip(&mut self, x: u8, y: u8) {
         let flipped = &mut self.0[x as usize][y as usize].as_mut().unwrap().flipped;
         *flipped = !*flipped;
     }
+
+    pub fn get(&self, x: u8, y: u8) -> Option<&CardProxy<T>> {
+        self.0.get(x as usize)?.get(y as usize)?.as_ref()
+    }
+
+    pub fn remove(&mut self, x: u8, y: u8) -> Option<CardProxy<T>> {
+        self.0.get_mut(x as usize)?.get_mut(y as usize)?.take()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.0.first().map_or(0, |r| r.len())
+    }
+
+    pub fn is_cleared(&self) -> bool {
+        self.0.iter().flatten().all(|c| c.is_none())
+    }
+
+    /// A copy with every face-down card replaced by the same placeholder, safe
+    /// to hand to someone who shouldn't see it.
+    pub fn masked(&self) -> Self {
+        let mut board = self.clone();
+        for c in board.0.iter_mut().flatten().flatten() {
+            if c.flipped {
+                c.card = T::placeholder();
+            }
+        }
+        board
+    }
+
+    /// Terminal columns the widest tile on the board takes up.
+    pub fn tile_width(&self) -> usize {
+        self.slots().map(|(_, c)| c.card.width()).max().unwrap_or(0)
+    }
+
+    /// Width of one slot in the board's text form, never narrower than a
+    /// playing card.
+    pub fn cell_width(&self) -> usize {
+        self.tile_width().max(3)
+    }
+
+    /// Every card still on the board along with its position.
+    pub fn slots(&self) -> impl Iterator<Item = ((u8, u8), &CardProxy<T>)> {
+        self.0.iter().enumerate().flat_map(|(x, row)| {
+            row.iter()
+                .enumerate()
+                .filter_map(move |(y, c)| Some(((x as u8, y as u8), c.as_ref()?)))
+        })
+    }
 }
 
-pub struct Player<'a> {
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Player<T = AnyTile> {
     pub name: String,
-    pub paired_cards: Vec<&'a cards::Card>,
+    pub paired_cards: Vec<T>,
+    #[serde(default)]
+    pub bot: Option<ai::Difficulty>,
+    #[serde(default)]
+    pub misses: u32,
 }
 
-impl Player<'_> {
+impl<T> Player<T> {
     pub fn new(name: String) -> Self {
         Self {
             name,
             paired_cards: vec![],
+            bot: None,
+            misses: 0,
         }
     }
+
+    pub fn bot(name: String, difficulty: ai::Difficulty) -> Self {
+        Self {
+            bot: Some(difficulty),
+            ..Self::new(name)
+        }
+    }
+
+    pub fn is_bot(&self) -> bool {
+        self.bot.is_some()
+    }
+
+    pub fn pairs(&self) -> usize {
+        self.paired_cards.len() / 2
+    }
+
+    /// Turns over pairs of cards taken, matched or not.
+    pub fn moves(&self) -> usize {
+        self.pairs() + self.misses as usize
+    }
 }
@@ -0,0 +1,179 @@
+use super::{
+    game::{Game, Pos, Reveal},
+    utils,
+};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+    Reveal { player: usize, pos: Pos },
+    Match { player: usize, a: Pos, b: Pos },
+    TurnChange { player: usize },
+    Forfeit { player: usize },
+    TimeUp,
+    Undo,
+    Redo,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    pub at_ms: u64,
+    pub kind: EventKind,
+}
+
+/// Everything needed to watch a game again: the game as it was dealt, and
+/// every move made since, timed from when it was dealt.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub recorded_at: u64,
+    start: Game,
+    events: Vec<Event>,
+    #[serde(skip)]
+    clock: Option<(Instant, u64)>,
+}
+
+impl Replay {
+    pub const VERSION: u32 = 1;
+
+    pub fn new(game: &Game) -> Self {
+        Self {
+            version: Self::VERSION,
+            recorded_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            start: game.clone(),
+            events: vec![],
+            clock: Some((Instant::now(), 0)),
+        }
+    }
+
+    /// Restarts the clock of a replay loaded from disk where its last event
+    /// left off, e.g. when its game is continued.
+    pub fn resume(&mut self) {
+        let last = self.events.last().map_or(0, |e| e.at_ms);
+        self.clock = Some((Instant::now(), last));
+    }
+
+    /// Milliseconds since recording started. A replay loaded from disk that
+    /// wasn't resumed starts its clock at its first new event.
+    fn now_ms(&mut self) -> u64 {
+        let last = self.events.last().map_or(0, |e| e.at_ms);
+        let (since, base) = *self.clock.get_or_insert((Instant::now(), last));
+        base + since.elapsed().as_millis() as u64
+    }
+
+    fn push(&mut self, kind: EventKind) {
+        let at_ms = self.now_ms();
+        self.events.push(Event { at_ms, kind });
+    }
+
+    /// Call right after a successful [`Game::reveal`].
+    pub fn record(&mut self, game: &Game, reveal: &Reveal) {
+        let player = game.turn();
+        match *reveal {
+            Reveal::First(pos) | Reveal::Mismatch(_, pos) => {
+                self.push(EventKind::Reveal { player, pos })
+            }
+            Reveal::Match(a, b) => {
+                self.push(EventKind::Reveal { player, pos: b });
+                self.push(EventKind::Match { player, a, b });
+            }
+        }
+    }
+
+    /// Call right after a successful [`Game::end_turn`].
+    pub fn record_turn_change(&mut self, game: &Game) {
+        self.push(EventKind::TurnChange {
+            player: game.turn(),
+        });
+    }
+
+    /// Call after a successful [`Game::forfeit_turn`], with the player who
+    /// gave the turn up.
+    pub fn record_forfeit(&mut self, player: usize) {
+        self.push(EventKind::Forfeit { player });
+    }
+
+    pub fn record_time_up(&mut self) {
+        self.push(EventKind::TimeUp);
+    }
+
+    pub fn record_undo(&mut self) {
+        self.push(EventKind::Undo);
+    }
+
+    pub fn record_redo(&mut self) {
+        self.push(EventKind::Redo);
+    }
+
+    pub fn start(&self) -> &Game {
+        &self.start
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// The game as it stood after the first `n` events.
+    pub fn state_at(&self, n: usize) -> Game {
+        let mut game = self.start.clone();
+        for event in self.events.iter().take(n) {
+            match event.kind {
+                EventKind::Reveal { pos, .. } => {
+                    let _ = game.reveal(pos);
+                }
+                EventKind::TurnChange { .. } => {
+                    let _ = game.end_turn();
+                }
+                EventKind::Forfeit { .. } => {
+                    let _ = game.forfeit_turn();
+                }
+                EventKind::TimeUp => game.time_up(),
+                EventKind::Undo => {
+                    let _ = game.undo();
+                }
+                EventKind::Redo => {
+                    let _ = game.redo();
+                }
+                EventKind::Match { .. } => {}
+            }
+        }
+        game
+    }
+
+    /// Time from the start of recording to the last move.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_millis(self.events.last().map_or(0, |e| e.at_ms))
+    }
+
+    /// Time between event `n` and the one before it.
+    pub fn gap_before(&self, n: usize) -> Duration {
+        let at = |i: usize| self.events.get(i).map_or(0, |e| e.at_ms);
+        let prev = n.checked_sub(1).map_or(0, at);
+        Duration::from_millis(at(n).saturating_sub(prev))
+    }
+
+    /// A one-line account of `event`, e.g. "alice turns over b3".
+    pub fn describe(&self, event: &Event) -> String {
+        let name = |i: usize| self.start.players().get(i).map_or("?", |p| p.name.as_str());
+        match event.kind {
+            EventKind::Reveal { player, pos } => {
+                format!("{} turns over {}", name(player), utils::pos_label(pos))
+            }
+            EventKind::Match { player, a, b } => format!(
+                "{} matches {} and {}",
+                name(player),
+                utils::pos_label(a),
+                utils::pos_label(b)
+            ),
+            EventKind::TurnChange { player } => format!("{}'s turn", name(player)),
+            EventKind::Forfeit { player } => format!("{} ran out of time", name(player)),
+            EventKind::TimeUp => "Time's up".to_owned(),
+            EventKind::Undo => "Move taken back".to_owned(),
+            EventKind::Redo => "Move redone".to_owned(),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use super::{
+    cards::Card,
+    tiles::{AnyTile, Tile},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub trait PairRule<T = Card> {
+    fn is_pair(&self, a: &T, b: &T) -> bool;
+}
+
+impl<T, F: Fn(&T, &T) -> bool> PairRule<T> for F {
+    fn is_pair(&self, a: &T, b: &T) -> bool {
+        self(a, b)
+    }
+}
+
+/// Tiles pair when their pair keys match.
+pub struct SameKey;
+
+impl<T: Tile> PairRule<T> for SameKey {
+    fn is_pair(&self, a: &T, b: &T) -> bool {
+        a.pair_key() == b.pair_key()
+    }
+}
+
+pub struct SameRankAndColor;
+
+impl PairRule for SameRankAndColor {
+    fn is_pair(&self, a: &Card, b: &Card) -> bool {
+        a.pairs_with(b)
+    }
+}
+
+pub struct SameRank;
+
+impl PairRule for SameRank {
+    fn is_pair(&self, a: &Card, b: &Card) -> bool {
+        a.rank() == b.rank()
+    }
+}
+
+/// Ranks pair off in sequence (A-2, 3-4, ..., J-Q) so that every card has
+/// exactly one partner per suit; kings are left over, and jokers pair with
+/// each other.
+pub struct SameSuitAdjacentRank;
+
+impl PairRule for SameSuitAdjacentRank {
+    fn is_pair(&self, a: &Card, b: &Card) -> bool {
+        if a.is_joker() || b.is_joker() {
+            return a.is_joker() && b.is_joker();
+        }
+        let (a_rank, b_rank) = (a.rank() as u8, b.rank() as u8);
+        a.suit() == b.suit() && a_rank != b_rank && a_rank / 2 == b_rank / 2
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PairRuleKind {
+    #[default]
+    RankAndColor,
+    RankOnly,
+    SuitAndAdjacentRank,
+}
+
+impl PairRuleKind {
+    pub const ALL: [PairRuleKind; 3] = [
+        PairRuleKind::RankAndColor,
+        PairRuleKind::RankOnly,
+        PairRuleKind::SuitAndAdjacentRank,
+    ];
+}
+
+impl PairRule for PairRuleKind {
+    fn is_pair(&self, a: &Card, b: &Card) -> bool {
+        match self {
+            PairRuleKind::RankAndColor => SameRankAndColor.is_pair(a, b),
+            PairRuleKind::RankOnly => SameRank.is_pair(a, b),
+            PairRuleKind::SuitAndAdjacentRank => SameSuitAdjacentRank.is_pair(a, b),
+        }
+    }
+}
+
+/// Playing cards pair by the rule, anything else by [`SameKey`].
+impl PairRule<AnyTile> for PairRuleKind {
+    fn is_pair(&self, a: &AnyTile, b: &AnyTile) -> bool {
+        match (a, b) {
+            (AnyTile::Card(a), AnyTile::Card(b)) => PairRule::<Card>::is_pair(self, a, b),
+            _ => SameKey.is_pair(a, b),
+        }
+    }
+}
+
+impl fmt::Display for PairRuleKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            PairRuleKind::RankAndColor => "Same rank & color",
+            PairRuleKind::RankOnly => "Same rank",
+            PairRuleKind::SuitAndAdjacentRank => "Same suit, adjacent rank",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cards::{Deck, DeckKind, DeckSpec, Rank, Suit};
+
+    #[test]
+    fn adjacent_ranks_pair_within_a_suit() {
+        let hearts = |rank| Card::new(Suit::Hearts, rank);
+        let rule = SameSuitAdjacentRank;
+        for (a, b) in [
+            (Rank::Ace, Rank::Two),
+            (Rank::Three, Rank::Four),
+            (Rank::Nine, Rank::Ten),
+            (Rank::Jack, Rank::Queen),
+        ] {
+            assert!(rule.is_pair(&hearts(a), &hearts(b)), "{a:?} {b:?}");
+            assert!(rule.is_pair(&hearts(b), &hearts(a)), "{b:?} {a:?}");
+        }
+        assert!(!rule.is_pair(&hearts(Rank::Two), &hearts(Rank::Three)));
+        assert!(!rule.is_pair(&hearts(Rank::Ace), &hearts(Rank::Ace)));
+        assert!(!rule.is_pair(&hearts(Rank::Queen), &hearts(Rank::King)));
+        let two_of_spades = Card::new(Suit::Spades, Rank::Two);
+        assert!(!rule.is_pair(&hearts(Rank::Ace), &two_of_spades));
+    }
+
+    #[test]
+    fn kings_are_left_over_by_adjacent_ranks() {
+        let rule = PairRuleKind::SuitAndAdjacentRank;
+        let deck = Deck::new().into_paired_by(&rule);
+        let pairs = deck.pair_count(&rule);
+        assert_eq!(pairs, 24);
+        assert!(deck[..pairs * 2].iter().all(|c| c.rank() != Rank::King));
+        assert!(deck[pairs * 2..].iter().all(|c| c.rank() == Rank::King));
+        assert_eq!(deck.len() - pairs * 2, 4);
+    }
+
+    #[test]
+    fn jokers_pair_with_each_other_under_every_rule() {
+        let (red, black) = (Card::joker(Suit::Hearts), Card::joker(Suit::Spades));
+        let ace = Card::new(Suit::Hearts, Rank::Ace);
+        for rule in PairRuleKind::ALL {
+            assert!(rule.is_pair(&red, &black), "{rule:?}");
+            assert!(rule.is_pair(&red, &red), "{rule:?}");
+            assert!(!rule.is_pair(&red, &ace), "{rule:?}");
+            assert!(!rule.is_pair(&ace, &black), "{rule:?}");
+
+            for kind in DeckKind::ALL {
+                let spec = |jokers| DeckSpec {
+                    kind,
+                    decks: 1,
+                    jokers,
+                };
+                let pairs = |spec: DeckSpec| {
+                    Deck::from_spec(&spec)
+                        .into_paired_by(&rule)
+                        .pair_count(&rule)
+                };
+                assert_eq!(pairs(spec(2)), pairs(spec(0)) + 1, "{kind:?} {rule:?}");
+                assert_eq!(pairs(spec(4)), pairs(spec(0)) + 2, "{kind:?} {rule:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use super::cards::{Card, Deck, Rank, Suit};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use unicode_width::UnicodeWidthStr;
+
+/// Anything that can lie face down on the board waiting to be paired.
+pub trait Tile: Clone + fmt::Display {
+    /// Tiles with the same key pair up, unless a pair rule says otherwise.
+    fn pair_key(&self) -> String;
+
+    /// Terminal columns the face takes up.
+    fn width(&self) -> usize {
+        self.to_string().width()
+    }
+
+    /// What a face-down tile is swapped for on a masked board.
+    fn placeholder() -> Self;
+}
+
+impl Tile for Card {
+    fn pair_key(&self) -> String {
+        if self.is_joker() {
+            return self.rank().to_string();
+        }
+        format!("{}{:?}", self.rank(), self.color())
+    }
+
+    fn width(&self) -> usize {
+        3
+    }
+
+    fn placeholder() -> Self {
+        Card::new(Suit::Clubs, Rank::Ace)
+    }
+}
+
+/// A playing card, a symbol from a tile set or a tile from a deck file.
+/// Symbols are saved as plain strings, so games saved with cards read back the
+/// same as before.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AnyTile {
+    Card(Card),
+    Symbol(String),
+    Paired(PairedTile),
+}
+
+/// One half of a pair from a deck file. The halves can differ, like a word
+/// and its translation, and pair by the key they share.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PairedTile {
+    pub text: String,
+    pub key: String,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+impl fmt::Display for AnyTile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnyTile::Card(card) => write!(f, "{card}"),
+            AnyTile::Symbol(s) => write!(f, "{s}"),
+            AnyTile::Paired(tile) => write!(f, "{}", tile.text),
+        }
+    }
+}
+
+impl Tile for AnyTile {
+    fn pair_key(&self) -> String {
+        match self {
+            AnyTile::Card(card) => card.pair_key(),
+            AnyTile::Symbol(s) => s.clone(),
+            AnyTile::Paired(tile) => tile.key.clone(),
+        }
+    }
+
+    fn width(&self) -> usize {
+        match self {
+            AnyTile::Card(card) => card.width(),
+            AnyTile::Symbol(s) => s.width(),
+            AnyTile::Paired(tile) => tile.text.width(),
+        }
+    }
+
+    fn placeholder() -> Self {
+        AnyTile::Card(Card::placeholder())
+    }
+}
+
+impl From<Card> for AnyTile {
+    fn from(card: Card) -> Self {
+        AnyTile::Card(card)
+    }
+}
+
+pub const EMOJI: [&str; 48] = [
+    "🍎", "🍌", "🍇", "🍓", "🍒", "🍑", "🍍", "🥝", "🥕", "🌽", "🍄", "🥨", "🧀", "🍩", "🍪", "🎂",
+    "🐶", "🐱", "🐭", "🐰", "🦊", "🐻", "🐼", "🐨", "🐯", "🦁", "🐮", "🐷", "🐸", "🐵", "🐔", "🐧",
+    "🌞", "🌙", "⭐", "🌈", "🔥", "💧", "🌵", "🌻", "🚗", "🚀", "⛵", "🎈", "🎸", "⚽", "🎲", "🔑",
+];
+
+pub const WORDS: [&str; 48] = [
+    "apple", "bread", "cloud", "dream", "eagle", "flame", "grape", "honey", "igloo", "jelly",
+    "kite", "lemon", "maple", "night", "ocean", "piano", "quilt", "river", "stone", "tiger",
+    "umbra", "violin", "whale", "xenon", "yacht", "zebra", "amber", "brick", "cedar", "daisy",
+    "ember", "frost", "ghost", "hazel", "ivory", "jade", "koala", "lotus", "mango", "nutmeg",
+    "olive", "pearl", "quartz", "raven", "salt", "tulip", "velvet", "willow",
+];
+
+/// `A` to `Z`.
+pub fn letters() -> Vec<String> {
+    ('A'..='Z').map(String::from).collect_vec()
+}
+
+/// `1` to `99`.
+pub fn numbers() -> Vec<String> {
+    (1..=99).map(|n| n.to_string()).collect_vec()
+}
+
+/// A deck holding each symbol twice, pairs side by side.
+pub fn symbol_deck(symbols: &[String]) -> Deck<AnyTile> {
+    Deck::from_pairs(
+        symbols
+            .iter()
+            .unique()
+            .map(|s| [AnyTile::Symbol(s.clone()), AnyTile::Symbol(s.clone())]),
+    )
+}
@@ -1,4 +1,7 @@
 use super::extras;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn squarest_rect_with_even_area(n: u32) -> [u32; 2] {
     let area = 2 * n;
@@ -13,3 +16,83 @@ pub fn squarest_rect_with_even_area(n: u32) -> [u32; 2] {
     }
     [factors[mid]; 2]
 }
+
+/// Rows are labelled by letter, so a board has at most this many.
+pub const MAX_ROWS: usize = 26;
+
+/// Picks a column count for a freshly dealt board of `cards` cards, in at most
+/// [`MAX_ROWS`] rows. It depends on the card count alone, so a seed deals the
+/// same layout everywhere: about half again as wide as tall, with as few empty
+/// slots in the last row as that allows.
+pub fn deal_cols(cards: usize) -> usize {
+    let ideal = (cards as f64 * 1.5).sqrt();
+    let min = ((ideal * 0.75) as usize)
+        .max(cards.div_ceil(MAX_ROWS))
+        .max(1);
+    let max = ((ideal * 1.25).ceil() as usize).max(min);
+    (min..=max)
+        .min_by(|&a, &b| {
+            let empty = |cols: usize| cols * cards.div_ceil(cols) - cards;
+            let off = |cols: usize| (cols as f64 - ideal).abs();
+            empty(a).cmp(&empty(b)).then(off(a).total_cmp(&off(b)))
+        })
+        .unwrap_or(min)
+}
+
+/// Picks a column count for laying out `cards` cards in at most `max_cols`
+/// columns and `max_rows` rows, or `None` if no layout fits. Prefers layouts
+/// with fewer empty slots in the last row, then the one closest in shape to
+/// the space available.
+pub fn grid_to_fit(cards: usize, max_cols: usize, max_rows: usize) -> Option<usize> {
+    let max_rows = max_rows.min(MAX_ROWS);
+    let aspect = max_cols as f64 / max_rows.max(1) as f64;
+    (1..=max_cols.min(cards))
+        .map(|cols| (cols, cards.div_ceil(cols)))
+        .filter(|&(_, rows)| rows <= max_rows)
+        .min_by(|&(a_cols, a_rows), &(b_cols, b_rows)| {
+            let empty = |cols: usize, rows: usize| cols * rows - cards;
+            let skew = |cols: usize, rows: usize| (cols as f64 / rows as f64 / aspect).ln().abs();
+            empty(a_cols, a_rows)
+                .cmp(&empty(b_cols, b_rows))
+                .then(skew(a_cols, a_rows).total_cmp(&skew(b_cols, b_rows)))
+        })
+        .map(|(cols, _)| cols)
+}
+
+/// A portable RNG, so the same seed deals the same board on every platform
+/// and `rand` release.
+pub fn seeded_rng(seed: u64) -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(seed)
+}
+
+pub fn random_seed() -> u64 {
+    thread_rng().gen()
+}
+
+/// The same seed for everyone on a given (UTC) day.
+pub fn daily_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86_400)
+}
+
+/// The letter row `x` is labelled with. Boards have at most [`MAX_ROWS`]
+/// rows, so later rows all share the last letter.
+pub fn row_label(x: usize) -> char {
+    (b'a' + x.min(MAX_ROWS - 1) as u8) as char
+}
+
+/// Names a board position by row letter and 1-based column, so `(1, 2)` is
+/// `b3`.
+pub fn pos_label((x, y): (u8, u8)) -> String {
+    format!("{}{}", row_label(x as usize), y as u16 + 1)
+}
+
+/// Reads a position written the way [`pos_label`] writes it.
+pub fn parse_pos(s: &str) -> Option<(u8, u8)> {
+    let s = s.trim().to_ascii_lowercase();
+    let mut chars = s.chars();
+    let row = chars.next().filter(|c| c.is_ascii_lowercase())?;
+    let col = chars.as_str().parse::<u8>().ok()?.checked_sub(1)?;
+    Some((row as u8 - b'a', col))
+}
@@ -1,13 +1,18 @@
 use std::io;
 
-use app::{run_app, App};
+use app::{run_app, settings::Settings, state::PopupMsg, App};
 use crossterm::{event, execute, terminal};
 use tui::{backend::CrosstermBackend, Terminal};
 
 pub mod app;
+pub mod cli;
 pub mod core;
+pub mod line;
+pub mod net;
 
-pub fn start_ui() -> io::Result<()> {
+/// Runs the TUI until the player exits. `setup` can move the app off the
+/// title screen before the first frame, e.g. straight into a game.
+pub fn start_ui(setup: impl FnOnce(&mut App)) -> io::Result<()> {
     terminal::enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(
@@ -18,7 +23,13 @@ pub fn start_ui() -> io::Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let app = App::default();
+    let settings = Settings::load();
+    let mut app = App::new(settings.as_ref().cloned().unwrap_or_default());
+    if let Err(err) = settings {
+        app.show_popup(PopupMsg::err(format!("Failed to load settings: {err}")));
+    }
+    app.resize(terminal.size()?);
+    setup(&mut app);
     let res = run_app(&mut terminal, app);
 
     terminal::disable_raw_mode()?;
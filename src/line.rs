@@ -0,0 +1,291 @@
+use crate::{
+    app::{
+        save,
+        stats::{self, GameRecord, Stats},
+    },
+    core::{
+        ai::Bot,
+        game::{Game, Phase, Reveal},
+        replay::Replay,
+        utils,
+    },
+};
+use itertools::Itertools;
+use std::io::{self, BufRead, Write};
+
+fn print_board<W: Write>(out: &mut W, game: &Game) -> io::Result<()> {
+    let board = game.board();
+    let width = board.cell_width();
+    writeln!(
+        out,
+        "   {}",
+        (1..=board.cols()).map(|y| format!("{y:>width$}")).join(" ")
+    )?;
+    for (x, line) in board.to_string().lines().enumerate() {
+        writeln!(out, "{}  {line}", utils::row_label(x))?;
+    }
+    Ok(())
+}
+
+fn print_scores<W: Write>(out: &mut W, game: &Game) -> io::Result<()> {
+    let scores = game
+        .players()
+        .iter()
+        .map(|p| format!("{} {}", p.name, p.pairs()))
+        .join(", ");
+    writeln!(out, "Pairs: {scores}")
+}
+
+/// Runs a game over plain lines of text: the board is printed with its
+/// `Display` form and moves are read as positions like `b3`, one per line.
+pub fn run<I: BufRead, W: Write>(mut game: Game, input: I, mut out: W) -> io::Result<()> {
+    let mut bots = game
+        .players()
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            p.bot
+                .map(|d| Bot::new(d, game.seed().wrapping_add(i as u64)))
+        })
+        .collect_vec();
+    let mut lines = input.lines();
+    let mut replay = Replay::new(&game);
+
+    writeln!(
+        out,
+        "Rows are lettered from the top, columns numbered from the left. Type a position such as b3, or q to quit."
+    )?;
+    if game.is_practice() {
+        writeln!(out, "Type undo or redo to take back a move.")?;
+    }
+    let mut show_board = true;
+    while !game.is_over() {
+        if show_board && matches!(game.phase(), Phase::FirstPick) {
+            print_board(&mut out, &game)?;
+        }
+        show_board = true;
+
+        let turn = game.turn();
+        let pos = match bots[turn].as_mut() {
+            Some(bot) => bot.choose(&game),
+            None => {
+                write!(out, "{}> ", game.current_player().name)?;
+                out.flush()?;
+                let Some(line) = lines.next().transpose()? else {
+                    return Ok(());
+                };
+                match line.trim() {
+                    "q" | "quit" => return Ok(()),
+                    "" => {
+                        show_board = false;
+                        continue;
+                    }
+                    "board" => continue,
+                    cmd @ ("undo" | "redo") => {
+                        let redo = cmd == "redo";
+                        let step = |game: &mut Game| if redo { game.redo() } else { game.undo() };
+                        if let Err(err) = step(&mut game) {
+                            writeln!(out, "{err}")?;
+                            show_board = false;
+                            continue;
+                        }
+                        // Mismatches are flipped back as soon as they happen here,
+                        // so step past them too.
+                        let mut steps = 1;
+                        if let Phase::Mismatch(..) = game.phase() {
+                            if step(&mut game).is_ok() {
+                                steps += 1;
+                            }
+                        }
+                        for _ in 0..steps {
+                            if redo {
+                                replay.record_redo();
+                            } else {
+                                replay.record_undo();
+                            }
+                        }
+                        print_board(&mut out, &game)?;
+                        show_board = false;
+                        continue;
+                    }
+                    "scores" => {
+                        print_scores(&mut out, &game)?;
+                        show_board = false;
+                        continue;
+                    }
+                    s => match utils::parse_pos(s) {
+                        Some(pos) => Some(pos),
+                        None => {
+                            writeln!(out, "Not a position: {s}")?;
+                            show_board = false;
+                            continue;
+                        }
+                    },
+                }
+            }
+        };
+        let Some(pos) = pos else { break };
+
+        let name = game.current_player().name.clone();
+        let card = game.board().get(pos.0, pos.1).map(|c| c.card().to_string());
+        let res = match game.reveal(pos) {
+            Ok(res) => res,
+            Err(err) => {
+                writeln!(out, "{err}")?;
+                show_board = false;
+                continue;
+            }
+        };
+        replay.record(&game, &res);
+        let card = card.unwrap_or_default();
+        writeln!(
+            out,
+            "{name} turns over {}: {}",
+            utils::pos_label(pos),
+            card.trim()
+        )?;
+        for bot in bots.iter_mut().flatten() {
+            bot.observe(&game);
+        }
+        match res {
+            Reveal::Match(..) if !game.is_over() => writeln!(out, "Match! {name} goes again.")?,
+            Reveal::Mismatch(..) => {
+                // The cards are on screen now, so there's no need to wait before
+                // turning them back.
+                print_board(&mut out, &game)?;
+                writeln!(out, "No match.")?;
+                if game.end_turn().is_ok() {
+                    replay.record_turn_change(&game);
+                }
+                for bot in bots.iter_mut().flatten() {
+                    bot.observe(&game);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if game.is_over() {
+        let winners = game.winners().iter().map(|p| p.name.as_str()).join(", ");
+        writeln!(out, "Game over! Winner: {winners}.")?;
+        print_scores(&mut out, &game)?;
+        if let Err(err) = Stats::record(GameRecord::new(&game, replay.elapsed())) {
+            writeln!(out, "Failed to record stats: {err}")?;
+        }
+        match save::save_replay(&replay) {
+            Ok(path) => writeln!(out, "Replay saved to {}.", path.display())?,
+            Err(err) => writeln!(out, "Failed to save replay: {err}")?,
+        }
+    }
+    Ok(())
+}
+
+/// Prints every event of a replay on its own line, with the time it happened.
+pub fn print_replay<W: Write>(replay: &Replay, mut out: W) -> io::Result<()> {
+    for (i, event) in replay.events().iter().enumerate() {
+        let secs = event.at_ms / 1000;
+        writeln!(
+            out,
+            "{:>4}  {:02}:{:02}.{}  {}",
+            i + 1,
+            secs / 60,
+            secs % 60,
+            event.at_ms % 1000 / 100,
+            replay.describe(event)
+        )?;
+    }
+    let game = replay.state_at(replay.events().len());
+    if game.is_over() {
+        let winners = game.winners().iter().map(|p| p.name.as_str()).join(", ");
+        writeln!(out, "Winner: {winners}.")?;
+    }
+    print_scores(&mut out, &game)
+}
+
+/// Prints the leaderboard as two plain tables.
+pub fn print_stats<W: Write>(stats: &Stats, mut out: W) -> io::Result<()> {
+    writeln!(out, "{} games recorded.", stats.games.len())?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:<20} {:>6} {:>4} {:>8} {:>8}",
+        "Player", "Played", "Won", "Win rate", "Accuracy"
+    )?;
+    for p in stats.players() {
+        let name = if p.is_bot {
+            format!("{} (bot)", p.name)
+        } else {
+            p.name.clone()
+        };
+        writeln!(
+            out,
+            "{:<20} {:>6} {:>4} {:>7.0}% {:>7.0}%",
+            name,
+            p.played,
+            p.won,
+            p.win_rate() * 100.0,
+            p.accuracy() * 100.0
+        )?;
+    }
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:<10} {:>10}  {:<20} Seed",
+        "Board", "Best time", "Player"
+    )?;
+    for t in stats.best_times() {
+        writeln!(
+            out,
+            "{:<10} {:>10}  {:<20} {}",
+            format!("{} pairs", t.board_size),
+            stats::format_time(t.time_ms),
+            t.name,
+            t.seed
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::game::fixtures::game;
+    use std::io::Cursor;
+
+    #[test]
+    fn plays_from_scripted_lines() {
+        // Seed 1 deals A B over B A, so a1 and b2 are the pair.
+        let game = game(&["Ann"], &["A", "B"]);
+        let script = "a1\na2\nundo\nredo\nundo\nb2\nzz\nq\n";
+        let mut out = vec![];
+        run(game, Cursor::new(script), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let (hidden, a_up, both_up, a_taken) = (
+            ["     1   2", "a  --- ---", "b  --- ---"],
+            ["     1   2", "a    A ---", "b  --- ---"],
+            ["     1   2", "a    A   B", "b  --- ---"],
+            ["     1   2", "a      ---", "b  ---"],
+        );
+        let expected = [
+            &["Rows are lettered from the top, columns numbered from the left. Type a position such as b3, or q to quit."][..],
+            &["Type undo or redo to take back a move."],
+            &hidden,
+            &["Ann> Ann turns over a1: A", "Ann> Ann turns over a2: B"],
+            &both_up,
+            &["No match."],
+            &hidden,
+            // Undo steps back past the mismatch to the first card.
+            &["Ann>      1   2"],
+            &a_up[1..],
+            &["Ann>      1   2"],
+            &hidden[1..],
+            &["Ann>      1   2"],
+            &a_up[1..],
+            &["Ann> Ann turns over b2: A", "Match! Ann goes again."],
+            &a_taken,
+            &["Ann> Not a position: zz", "Ann>"],
+        ]
+        .concat();
+        assert_eq!(out.lines().map(str::trim_end).collect_vec(), expected);
+    }
+}
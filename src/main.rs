@@ -1,7 +1,13 @@
-use pairs_rs::start_ui;
-use std::error::Error;
+use clap::Parser;
+use pairs_rs::cli::{self, Cli};
+use std::process::ExitCode;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    start_ui()?;
-    Ok(())
+fn main() -> ExitCode {
+    match cli::run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("pairs: {err}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,402 @@
+use crate::core::game::{Game, Pos};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// How long the host waits on a client that stopped reading before giving up
+/// on it, so it can't hold up the game for everyone else.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMsg {
+    Join { name: String },
+    Reveal { pos: Pos },
+    FlipBack,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMsg {
+    Welcome { player: usize },
+    State { game: Game },
+    Error { msg: String },
+}
+
+/// Writes `msg` as a single line of JSON.
+fn send_line<T: Serialize>(stream: &mut TcpStream, msg: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(msg)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+/// Forwards every line read from `stream` through `tx` until the connection
+/// closes, at which point `None` is sent. Unparseable lines are skipped.
+fn spawn_reader<T, E, F>(stream: TcpStream, tx: Sender<E>, wrap: F)
+where
+    T: DeserializeOwned,
+    E: Send + 'static,
+    F: Fn(Option<T>) -> E + Send + 'static,
+{
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            if let Ok(msg) = serde_json::from_str(&line) {
+                if tx.send(wrap(Some(msg))).is_err() {
+                    return;
+                }
+            }
+        }
+        let _ = tx.send(wrap(None));
+    });
+}
+
+pub enum HostRequest {
+    Reveal { player: usize, pos: Pos },
+    FlipBack { player: usize },
+}
+
+struct Conn {
+    stream: TcpStream,
+    player: Option<usize>,
+}
+
+/// The authoritative side of a networked game. Remote clients claim a human
+/// seat by name; every other seat keeps being played on the host's terminal.
+pub struct Host {
+    listener: TcpListener,
+    conns: HashMap<usize, Conn>,
+    next_id: usize,
+    tx: Sender<(usize, Option<ClientMsg>)>,
+    rx: Receiver<(usize, Option<ClientMsg>)>,
+    last_state: String,
+}
+
+impl Host {
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        let (tx, rx) = mpsc::channel();
+        Ok(Self {
+            listener,
+            conns: HashMap::new(),
+            next_id: 0,
+            tx,
+            rx,
+            last_state: String::new(),
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.listener.local_addr().map_or(0, |a| a.port())
+    }
+
+    pub fn is_remote(&self, player: usize) -> bool {
+        self.conns.values().any(|c| c.player == Some(player))
+    }
+
+    fn accept(&mut self) {
+        while let Ok((stream, _)) = self.listener.accept() {
+            let Ok(reader) = stream
+                .set_nonblocking(false)
+                .and_then(|_| stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+                .and_then(|_| stream.try_clone())
+            else {
+                continue;
+            };
+            let id = self.next_id;
+            self.next_id += 1;
+            spawn_reader(reader, self.tx.clone(), move |msg| (id, msg));
+            self.conns.insert(
+                id,
+                Conn {
+                    stream,
+                    player: None,
+                },
+            );
+        }
+    }
+
+    fn join(&mut self, id: usize, name: &str, game: &Game) {
+        let seat = game.players().iter().position(|p| p.name == name);
+        let reply = match seat {
+            None => Err(format!("No player named \"{name}\" in this game.")),
+            Some(i) if game.players()[i].is_bot() => Err(format!("\"{name}\" is a bot.")),
+            Some(i) if self.is_remote(i) => Err(format!("\"{name}\" has already joined.")),
+            Some(i) => Ok(i),
+        };
+        let Some(conn) = self.conns.get_mut(&id) else {
+            return;
+        };
+        let res = match reply {
+            Ok(player) => {
+                conn.player = Some(player);
+                send_line(&mut conn.stream, &ServerMsg::Welcome { player }).and_then(|_| {
+                    let game = game.masked();
+                    send_line(&mut conn.stream, &ServerMsg::State { game })
+                })
+            }
+            Err(msg) => send_line(&mut conn.stream, &ServerMsg::Error { msg }),
+        };
+        if res.is_err() {
+            self.drop_conn(id);
+        }
+    }
+
+    fn drop_conn(&mut self, id: usize) {
+        if let Some(conn) = self.conns.remove(&id) {
+            let _ = conn.stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    /// Accepts new connections, seats joining players, and returns the moves
+    /// remote players asked for. Moves are not validated here beyond who sent
+    /// them.
+    pub fn poll(&mut self, game: &Game) -> Vec<HostRequest> {
+        self.accept();
+        let mut requests = vec![];
+        while let Ok((id, msg)) = self.rx.try_recv() {
+            let player = self.conns.get(&id).and_then(|c| c.player);
+            match (msg, player) {
+                (None, _) => self.drop_conn(id),
+                (Some(ClientMsg::Join { name }), None) => self.join(id, &name, game),
+                (Some(ClientMsg::Reveal { pos }), Some(player)) => {
+                    requests.push(HostRequest::Reveal { player, pos })
+                }
+                (Some(ClientMsg::FlipBack), Some(player)) => {
+                    requests.push(HostRequest::FlipBack { player })
+                }
+                _ => {}
+            }
+        }
+        requests
+    }
+
+    /// Sends the current state to every seated client if it changed since the
+    /// last broadcast.
+    pub fn broadcast(&mut self, game: &Game) {
+        let game = game.masked();
+        let Ok(state) = serde_json::to_string(&game) else {
+            return;
+        };
+        if state == self.last_state {
+            return;
+        }
+        self.last_state = state;
+        let msg = ServerMsg::State { game };
+        let failed = self
+            .conns
+            .iter_mut()
+            .filter(|(_, c)| c.player.is_some())
+            .filter_map(|(&id, c)| send_line(&mut c.stream, &msg).err().map(|_| id))
+            .collect::<Vec<_>>();
+        for id in failed {
+            self.drop_conn(id);
+        }
+    }
+}
+
+impl Drop for Host {
+    fn drop(&mut self) {
+        for conn in self.conns.values() {
+            let _ = conn.stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+pub enum ClientEvent {
+    Welcome(usize),
+    State(Game),
+    Error(String),
+    Disconnected,
+}
+
+pub struct Client {
+    stream: TcpStream,
+    rx: Receiver<Option<ServerMsg>>,
+    player: Option<usize>,
+}
+
+impl Client {
+    /// Connects to `addr`, adding the default port when none is given, and
+    /// asks to take the seat named `name`.
+    pub fn connect(addr: &str, name: &str) -> io::Result<Self> {
+        let addr = if addr.contains(':') {
+            addr.to_owned()
+        } else {
+            format!("{addr}:{DEFAULT_PORT}")
+        };
+        let addr = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "unknown host"))?;
+        let mut stream = TcpStream::connect_timeout(&addr, Duration::from_secs(3))?;
+        let (tx, rx) = mpsc::channel();
+        spawn_reader(stream.try_clone()?, tx, |msg| msg);
+        send_line(
+            &mut stream,
+            &ClientMsg::Join {
+                name: name.to_owned(),
+            },
+        )?;
+        Ok(Self {
+            stream,
+            rx,
+            player: None,
+        })
+    }
+
+    pub fn player(&self) -> Option<usize> {
+        self.player
+    }
+
+    pub fn poll(&mut self) -> Vec<ClientEvent> {
+        let mut events = vec![];
+        while let Ok(msg) = self.rx.try_recv() {
+            events.push(match msg {
+                None => ClientEvent::Disconnected,
+                Some(ServerMsg::Welcome { player }) => {
+                    self.player = Some(player);
+                    ClientEvent::Welcome(player)
+                }
+                Some(ServerMsg::State { game }) => ClientEvent::State(game),
+                Some(ServerMsg::Error { msg }) => ClientEvent::Error(msg),
+            });
+        }
+        events
+    }
+
+    pub fn reveal(&mut self, pos: Pos) -> io::Result<()> {
+        send_line(&mut self.stream, &ClientMsg::Reveal { pos })
+    }
+
+    pub fn flip_back(&mut self) -> io::Result<()> {
+        send_line(&mut self.stream, &ClientMsg::FlipBack)
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        game::fixtures::card_game,
+        tiles::{AnyTile, Tile},
+    };
+    use std::time::Instant;
+
+    /// Polls `f` until it returns something, failing the test after a while.
+    fn wait_for<T>(mut f: impl FnMut() -> Option<T>) -> T {
+        let start = Instant::now();
+        loop {
+            if let Some(v) = f() {
+                return v;
+            }
+            assert!(start.elapsed() < Duration::from_secs(5), "timed out");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn next_state(client: &mut Client) -> Game {
+        wait_for(|| {
+            client.poll().into_iter().find_map(|event| match event {
+                ClientEvent::State(game) => Some(game),
+                _ => None,
+            })
+        })
+    }
+
+    #[test]
+    fn join_and_play_over_localhost() {
+        let mut game = card_game(4);
+        let mut host = Host::bind(0).unwrap();
+        let mut client = Client::connect(&format!("127.0.0.1:{}", host.port()), "Bob").unwrap();
+
+        let state = wait_for(|| {
+            host.poll(&game);
+            let events = client.poll();
+            assert!(events.iter().all(|e| !matches!(e, ClientEvent::Error(_))));
+            events.into_iter().find_map(|event| match event {
+                ClientEvent::State(game) => Some(game),
+                _ => None,
+            })
+        });
+        assert_eq!(client.player(), Some(1));
+        assert!(host.is_remote(1));
+        assert_eq!(state.seed(), 0);
+        assert!(state
+            .board()
+            .slots()
+            .all(|(_, c)| *c.card() == AnyTile::placeholder()));
+
+        game.reveal((0, 0)).unwrap();
+        host.broadcast(&game);
+        let state = next_state(&mut client);
+        assert_eq!(state.board().get(0, 0), game.board().get(0, 0));
+
+        client.reveal((1, 1)).unwrap();
+        let request = wait_for(|| host.poll(&game).into_iter().next());
+        assert!(matches!(
+            request,
+            HostRequest::Reveal {
+                player: 1,
+                pos: (1, 1)
+            }
+        ));
+    }
+
+    #[test]
+    fn unknown_names_are_turned_away() {
+        let game = card_game(4);
+        let mut host = Host::bind(0).unwrap();
+        let mut client = Client::connect(&format!("127.0.0.1:{}", host.port()), "Cy").unwrap();
+
+        let msg = wait_for(|| {
+            host.poll(&game);
+            client.poll().into_iter().find_map(|event| match event {
+                ClientEvent::Error(msg) => Some(msg),
+                _ => None,
+            })
+        });
+        assert_eq!(msg, "No player named \"Cy\" in this game.");
+        assert_eq!(client.player(), None);
+    }
+
+    #[test]
+    fn clients_that_stop_reading_are_dropped() {
+        let game = card_game(4);
+        let mut host = Host::bind(0).unwrap();
+        let mut stream = TcpStream::connect(("127.0.0.1", host.port())).unwrap();
+        let join = ClientMsg::Join { name: "Bob".into() };
+        send_line(&mut stream, &join).unwrap();
+        wait_for(|| {
+            host.poll(&game);
+            host.is_remote(1).then_some(())
+        });
+
+        // Nothing is ever read from `stream`, so the buffers fill up until a
+        // write times out.
+        let start = Instant::now();
+        while host.is_remote(1) {
+            assert!(start.elapsed() < Duration::from_secs(10), "never dropped");
+            let sent = Instant::now();
+            host.last_state.clear();
+            host.broadcast(&game);
+            assert!(sent.elapsed() < WRITE_TIMEOUT * 4);
+        }
+        assert!(host.conns.is_empty());
+    }
+}